@@ -1,5 +1,4 @@
 use obws::Client;
-use tokio;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
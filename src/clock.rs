@@ -0,0 +1,46 @@
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+use tokio::time::{Instant, Sleep};
+
+/// Source of wall-clock time and timers for the swapper and session state.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Defaults to tokio's timer, so a paused test runtime can auto-advance it.
+    fn sleep(&self, duration: Duration) -> Sleep {
+        tokio::time::sleep(duration)
+    }
+}
+
+/// The real system clock.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Wall-clock time derived from tokio's monotonic clock, starting at a fixed
+/// instant. Under `tokio::time::pause()` it only moves when tokio time moves,
+/// which makes swap timing fully deterministic in tests.
+pub struct TokioClock {
+    start: DateTime<Utc>,
+    started_at: Instant,
+}
+
+impl TokioClock {
+    pub fn starting_at(start: DateTime<Utc>) -> Self {
+        Self {
+            start,
+            started_at: Instant::now(),
+        }
+    }
+}
+
+impl Clock for TokioClock {
+    fn now(&self) -> DateTime<Utc> {
+        let elapsed = Instant::now() - self.started_at;
+        self.start + chrono::Duration::from_std(elapsed).unwrap_or_default()
+    }
+}
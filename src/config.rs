@@ -1,5 +1,8 @@
+use crate::duration::SwapDuration;
+use crate::keys::KeySequence;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 use std::sync::Arc;
@@ -9,29 +12,106 @@ use tokio::sync::RwLock;
 pub struct GameConfig {
     pub exe_name: String,
     pub display_name: String,
-    #[serde(default = "default_true")]
-    pub send_esc_on_leave: bool,
-    #[serde(default = "default_true")]
-    pub send_esc_on_enter: bool,
+    /// Keys sent to the game before swapping away from it, e.g. to pause it.
+    #[serde(default = "default_pause_keys")]
+    pub on_leave: KeySequence,
+    /// Keys sent to the game once it's swapped in, e.g. to unpause it.
+    #[serde(default = "default_pause_keys")]
+    pub on_enter: KeySequence,
+    /// Where `on_leave` and `on_enter` keys go.
+    #[serde(default)]
+    pub key_delivery: KeyDelivery,
+    /// How the game is paused while it's swapped out.
+    #[serde(default)]
+    pub pause_method: PauseMethod,
     #[serde(default = "default_true")]
     pub enabled: bool,
     #[serde(default)]
     pub obs_scene: Option<String>,
+    /// Relative chance of being picked with the weighted strategy.
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+    /// Overrides the global `min_swap` while this game is swapped in.
+    #[serde(default)]
+    pub min_swap: Option<SwapDuration>,
+    /// Overrides the global `max_swap` while this game is swapped in.
+    #[serde(default)]
+    pub max_swap: Option<SwapDuration>,
+    /// Total play time this game gets in the session. Once it's used up the
+    /// game leaves the rotation.
+    #[serde(default)]
+    pub time_budget: Option<SwapDuration>,
 }
 
 fn default_true() -> bool {
     true
 }
 
+fn default_pause_keys() -> KeySequence {
+    KeySequence::escape()
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+/// How a game's leave and enter keys are sent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyDelivery {
+    /// Injected like real key presses, so they go to whatever has focus.
+    #[default]
+    Global,
+    /// Posted straight to the game's window, even when it's not in front.
+    /// Falls back to `Global` when that isn't possible, e.g. when the window
+    /// is gone.
+    Window,
+}
+
+/// How a game is paused while another one is swapped in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PauseMethod {
+    /// Sends `on_leave` and `on_enter` keys.
+    #[default]
+    Keys,
+    /// Suspends the game's process while it's swapped out, for games
+    /// without a pause menu. The keys aren't sent.
+    Suspend,
+}
+
+/// How the next game is picked on an automatic swap.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelectionStrategy {
+    /// Uniformly random among the other games.
+    #[default]
+    Random,
+    /// Random, weighted by each game's `weight`.
+    Weighted,
+    /// The next game in config order.
+    RoundRobin,
+    /// Every game plays once, in random order, before any repeats.
+    ShuffleBag,
+    /// The game with the least total time played.
+    LeastPlayed,
+}
+
 impl Default for GameConfig {
     fn default() -> Self {
         Self {
             exe_name: String::new(),
             display_name: String::new(),
-            send_esc_on_leave: true,
-            send_esc_on_enter: true,
+            on_leave: default_pause_keys(),
+            on_enter: default_pause_keys(),
+            key_delivery: KeyDelivery::Global,
+            pause_method: PauseMethod::Keys,
             enabled: true,
             obs_scene: None,
+            weight: 1.0,
+            min_swap: None,
+            max_swap: None,
+            time_budget: None,
         }
     }
 }
@@ -41,12 +121,14 @@ pub struct AppConfig {
     #[serde(default)]
     pub games: Vec<GameConfig>,
     #[serde(default = "default_min_swap")]
-    pub min_swap_minutes: u32,
+    pub min_swap: SwapDuration,
     #[serde(default = "default_max_swap")]
-    pub max_swap_minutes: u32,
+    pub max_swap: SwapDuration,
     #[serde(default = "default_true")]
     pub auto_swap_enabled: bool,
     #[serde(default)]
+    pub selection_strategy: SelectionStrategy,
+    #[serde(default)]
     pub hide_next_swap: bool,
     #[serde(default = "default_obs_host")]
     pub obs_ws_host: String,
@@ -54,14 +136,32 @@ pub struct AppConfig {
     pub obs_ws_port: u16,
     #[serde(default)]
     pub obs_ws_password: Option<String>,
+    /// Line rendered per stint for the plain-text summary export.
+    #[serde(default = "default_summary_template")]
+    pub summary_template: String,
+    /// Seed for swap delays and game picks. The same seed and games give the
+    /// same schedule; without one, every session picks a random seed.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// How many upcoming swaps to plan ahead. 0 picks each game when its swap comes.
+    #[serde(default)]
+    pub schedule_ahead: u32,
+    /// How long to let a game's window settle after focusing it, in
+    /// milliseconds, before checking it's in the foreground.
+    #[serde(default = "default_focus_settle_ms")]
+    pub focus_settle_ms: u64,
+    /// How long a game gets to come to the front before the swap is given
+    /// up and no enter keys are sent, in milliseconds.
+    #[serde(default = "default_focus_timeout_ms")]
+    pub focus_timeout_ms: u64,
 }
 
-fn default_min_swap() -> u32 {
-    5
+fn default_min_swap() -> SwapDuration {
+    SwapDuration::from_minutes(5)
 }
 
-fn default_max_swap() -> u32 {
-    15
+fn default_max_swap() -> SwapDuration {
+    SwapDuration::from_minutes(15)
 }
 
 fn default_obs_host() -> String {
@@ -72,22 +172,55 @@ fn default_obs_port() -> u16 {
     4455
 }
 
+fn default_focus_settle_ms() -> u64 {
+    100
+}
+
+fn default_focus_timeout_ms() -> u64 {
+    2000
+}
+
+fn default_summary_template() -> String {
+    crate::export::DEFAULT_SUMMARY_TEMPLATE.to_string()
+}
+
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
             games: Vec::new(),
-            min_swap_minutes: 5,
-            max_swap_minutes: 15,
+            min_swap: default_min_swap(),
+            max_swap: default_max_swap(),
             auto_swap_enabled: true,
+            selection_strategy: SelectionStrategy::Random,
             hide_next_swap: false,
             obs_ws_host: "localhost".to_string(),
             obs_ws_port: 4455,
             obs_ws_password: None,
+            summary_template: default_summary_template(),
+            seed: None,
+            schedule_ahead: 0,
+            focus_settle_ms: default_focus_settle_ms(),
+            focus_timeout_ms: default_focus_timeout_ms(),
         }
     }
 }
 
 impl AppConfig {
+    pub fn game_by_exe(&self, exe_name: &str) -> Option<&GameConfig> {
+        self.games
+            .iter()
+            .find(|g| g.exe_name.eq_ignore_ascii_case(exe_name))
+    }
+
+    /// Time budgets in seconds of the enabled games, keyed by exe name.
+    pub fn time_budgets(&self) -> HashMap<String, u64> {
+        self.games
+            .iter()
+            .filter(|g| g.enabled)
+            .filter_map(|g| Some((g.exe_name.clone(), g.time_budget?.as_secs())))
+            .collect()
+    }
+
     pub fn load(path: &Path) -> Result<Self> {
         if !path.exists() {
             let config = Self::default();
@@ -98,7 +231,17 @@ impl AppConfig {
         let content = fs::read_to_string(path)
             .with_context(|| format!("Failed to read config file: {:?}", path))?;
 
-        toml::from_str(&content)
+        Self::parse(&content)
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut table: toml::Table =
+            toml::from_str(content).with_context(|| "Failed to parse config file")?;
+        migrate_minute_intervals(&mut table)?;
+        migrate_esc_toggles(&mut table)?;
+
+        table
+            .try_into()
             .with_context(|| "Failed to parse config file")
     }
 
@@ -116,6 +259,70 @@ impl AppConfig {
     }
 }
 
+/// Older configs stored swap intervals as whole minutes under
+/// `min_swap_minutes` / `max_swap_minutes`, globally and per game. Rewrite
+/// those keys to the second-precision `min_swap` / `max_swap`.
+fn migrate_minute_intervals(table: &mut toml::Table) -> Result<()> {
+    fn migrate(table: &mut toml::Table) -> Result<()> {
+        for (old, new) in [
+            ("min_swap_minutes", "min_swap"),
+            ("max_swap_minutes", "max_swap"),
+        ] {
+            let Some(value) = table.remove(old) else {
+                continue;
+            };
+            let minutes = value
+                .as_integer()
+                .filter(|m| *m >= 0)
+                .with_context(|| format!("{} must be a whole number of minutes", old))?;
+
+            if !table.contains_key(new) {
+                table.insert(new.to_string(), toml::Value::Integer(minutes * 60));
+            }
+        }
+        Ok(())
+    }
+
+    migrate(table)?;
+    if let Some(toml::Value::Array(games)) = table.get_mut("games") {
+        for game in games.iter_mut().filter_map(|g| g.as_table_mut()) {
+            migrate(game)?;
+        }
+    }
+    Ok(())
+}
+
+/// Turns the `send_esc_on_leave` / `send_esc_on_enter` toggles of older
+/// configs into `on_leave` / `on_enter` key sequences.
+fn migrate_esc_toggles(table: &mut toml::Table) -> Result<()> {
+    let Some(toml::Value::Array(games)) = table.get_mut("games") else {
+        return Ok(());
+    };
+    for game in games.iter_mut().filter_map(|g| g.as_table_mut()) {
+        for (old, new) in [
+            ("send_esc_on_leave", "on_leave"),
+            ("send_esc_on_enter", "on_enter"),
+        ] {
+            let Some(value) = game.remove(old) else {
+                continue;
+            };
+            let send_esc = value
+                .as_bool()
+                .with_context(|| format!("{} must be true or false", old))?;
+
+            if !game.contains_key(new) {
+                let keys = if send_esc {
+                    KeySequence::escape()
+                } else {
+                    KeySequence::default()
+                };
+                game.insert(new.to_string(), toml::Value::String(keys.to_string()));
+            }
+        }
+    }
+    Ok(())
+}
+
 pub struct ConfigManager {
     config: Arc<RwLock<AppConfig>>,
     path: std::path::PathBuf,
@@ -0,0 +1,366 @@
+use crate::config::AppConfig;
+use crate::export::GameTotal;
+use crate::state::{CompletedGame, SwapHistoryItem, SwapState, SwapTrigger};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Mutex;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS sessions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    started_at TEXT NOT NULL,
+    ended_at TEXT,
+    games TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS stints (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    session_id INTEGER NOT NULL REFERENCES sessions(id),
+    game_name TEXT NOT NULL,
+    exe_name TEXT NOT NULL,
+    started_at TEXT NOT NULL,
+    ended_at TEXT NOT NULL,
+    duration_seconds INTEGER NOT NULL,
+    paused_seconds INTEGER NOT NULL,
+    trigger TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS stints_session ON stints(session_id);
+CREATE TABLE IF NOT EXISTS completions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    session_id INTEGER NOT NULL REFERENCES sessions(id),
+    game_name TEXT NOT NULL,
+    exe_name TEXT NOT NULL,
+    completed_at TEXT NOT NULL,
+    total_seconds INTEGER NOT NULL
+);
+";
+
+/// A game that was in the rotation when a session started.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionGame {
+    pub exe_name: String,
+    pub display_name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SessionInfo {
+    pub id: i64,
+    pub name: String,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub games: Vec<SessionGame>,
+    pub stints: u64,
+    pub total_seconds: u64,
+}
+
+/// Per-game time across several sessions, in the order the sessions were asked for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GameComparison {
+    pub game_name: String,
+    pub seconds: Vec<u64>,
+}
+
+/// Sessions, their stints and completions in a local SQLite database.
+pub struct SessionDb {
+    conn: Mutex<Connection>,
+}
+
+impl SessionDb {
+    pub fn open(path: &Path) -> Result<Self> {
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open session database: {:?}", path))?;
+        Self::with_connection(conn)
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA)
+            .with_context(|| "Failed to set up session database")?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Records a new session with the config's enabled games and returns its id.
+    pub fn create_session(
+        &self,
+        name: &str,
+        started_at: DateTime<Utc>,
+        config: &AppConfig,
+    ) -> Result<i64> {
+        let games: Vec<SessionGame> = config
+            .games
+            .iter()
+            .filter(|g| g.enabled)
+            .map(|g| SessionGame {
+                exe_name: g.exe_name.clone(),
+                display_name: g.display_name.clone(),
+            })
+            .collect();
+
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO sessions (name, started_at, games) VALUES (?1, ?2, ?3)",
+            params![name, started_at, serde_json::to_string(&games)?],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Brings the database up to date with `state`'s session: stints and
+    /// completions it doesn't have yet, and the end time. Safe to call often.
+    pub fn sync(&self, state: &SwapState) -> Result<()> {
+        let Some(session_id) = state.session_id else {
+            return Ok(());
+        };
+
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        let mut stints: usize = tx.query_row(
+            "SELECT COUNT(*) FROM stints WHERE session_id = ?1",
+            [session_id],
+            |row| row.get(0),
+        )?;
+        if stints > state.history.len() {
+            // The stint `finish` closed early is running again in a resumed session
+            tx.execute(
+                "DELETE FROM stints WHERE session_id = ?1 AND id NOT IN
+                    (SELECT id FROM stints WHERE session_id = ?1 ORDER BY id LIMIT ?2)",
+                params![session_id, state.history.len()],
+            )?;
+            stints = state.history.len();
+        }
+        for item in state.history.iter().skip(stints) {
+            tx.execute(
+                "INSERT INTO stints (session_id, game_name, exe_name, started_at, ended_at,
+                    duration_seconds, paused_seconds, trigger)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    session_id,
+                    item.game_name,
+                    item.exe_name,
+                    item.started_at,
+                    item.ended_at,
+                    item.duration_seconds,
+                    item.paused_seconds,
+                    item.trigger.as_str(),
+                ],
+            )?;
+        }
+
+        let completions: usize = tx.query_row(
+            "SELECT COUNT(*) FROM completions WHERE session_id = ?1",
+            [session_id],
+            |row| row.get(0),
+        )?;
+        for completed in state.completed.iter().skip(completions) {
+            tx.execute(
+                "INSERT INTO completions (session_id, game_name, exe_name, completed_at, total_seconds)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    session_id,
+                    completed.game_name,
+                    completed.exe_name,
+                    completed.completed_at,
+                    completed.total_seconds,
+                ],
+            )?;
+        }
+
+        tx.execute(
+            "UPDATE sessions SET ended_at = ?1 WHERE id = ?2",
+            params![state.session_ended_at, session_id],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Records `state`'s session as ended at `now`, with the stint that was
+    /// still running, for a session that's left behind without being ended:
+    /// on shutdown, or when the previous one isn't resumed. Resuming it later
+    /// reopens it on the next `sync`.
+    pub fn finish(&self, state: &SwapState, now: DateTime<Utc>) -> Result<()> {
+        let mut state = state.clone();
+        if state.session_ended_at.is_none() {
+            state.close_stint(now);
+            state.session_ended_at = Some(now);
+        }
+        self.sync(&state)
+    }
+
+    /// Deletes the session if nothing was recorded in it, e.g. the fresh one
+    /// that gets replaced when an older session is resumed.
+    pub fn remove_if_empty(&self, id: i64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "DELETE FROM sessions WHERE id = ?1
+                AND NOT EXISTS (SELECT 1 FROM stints WHERE session_id = ?1)
+                AND NOT EXISTS (SELECT 1 FROM completions WHERE session_id = ?1)",
+            [id],
+        )?;
+        Ok(())
+    }
+
+    /// Every session, newest first.
+    pub fn sessions(&self) -> Result<Vec<SessionInfo>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT s.id, s.name, s.started_at, s.ended_at, s.games,
+                    COUNT(t.id), COALESCE(SUM(t.duration_seconds), 0)
+             FROM sessions s LEFT JOIN stints t ON t.session_id = s.id
+             GROUP BY s.id
+             ORDER BY s.started_at DESC, s.id DESC",
+        )?;
+        let sessions = stmt
+            .query_map([], session_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(sessions)
+    }
+
+    pub fn session(&self, id: i64) -> Result<Option<SessionInfo>> {
+        let conn = self.conn.lock().unwrap();
+        let session = conn
+            .query_row(
+                "SELECT s.id, s.name, s.started_at, s.ended_at, s.games,
+                    COUNT(t.id), COALESCE(SUM(t.duration_seconds), 0)
+             FROM sessions s LEFT JOIN stints t ON t.session_id = s.id
+             WHERE s.id = ?1
+             GROUP BY s.id",
+                [id],
+                session_from_row,
+            )
+            .optional()?;
+        Ok(session)
+    }
+
+    /// A page of the session's stints, newest first, and how many there are in total.
+    pub fn history(
+        &self,
+        id: i64,
+        offset: usize,
+        limit: usize,
+    ) -> Result<(usize, Vec<SwapHistoryItem>)> {
+        let conn = self.conn.lock().unwrap();
+        let total: usize = conn.query_row(
+            "SELECT COUNT(*) FROM stints WHERE session_id = ?1",
+            [id],
+            |row| row.get(0),
+        )?;
+
+        let mut stmt = conn.prepare(
+            "SELECT game_name, exe_name, started_at, ended_at, duration_seconds,
+                    paused_seconds, trigger
+             FROM stints WHERE session_id = ?1
+             ORDER BY id DESC LIMIT ?2 OFFSET ?3",
+        )?;
+        let items = stmt
+            .query_map(params![id, limit, offset], |row| {
+                let trigger: String = row.get(6)?;
+                Ok(SwapHistoryItem {
+                    game_name: row.get(0)?,
+                    exe_name: row.get(1)?,
+                    started_at: row.get(2)?,
+                    ended_at: row.get(3)?,
+                    duration_seconds: row.get(4)?,
+                    paused_seconds: row.get(5)?,
+                    trigger: match trigger.as_str() {
+                        "forced" => SwapTrigger::Forced,
+                        "queued" => SwapTrigger::Queued,
+                        "scripted" => SwapTrigger::Scripted,
+                        _ => SwapTrigger::Automatic,
+                    },
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok((total, items))
+    }
+
+    /// Per-game totals of the session, most played first.
+    pub fn totals(&self, id: i64) -> Result<Vec<GameTotal>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT game_name, SUM(duration_seconds), COUNT(*) FROM stints
+             WHERE session_id = ?1 GROUP BY game_name
+             ORDER BY SUM(duration_seconds) DESC, game_name",
+        )?;
+        let totals = stmt
+            .query_map([id], |row| {
+                Ok(GameTotal {
+                    game_name: row.get(0)?,
+                    total_seconds: row.get(1)?,
+                    stints: row.get(2)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(totals)
+    }
+
+    pub fn completions(&self, id: i64) -> Result<Vec<CompletedGame>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT game_name, exe_name, completed_at, total_seconds FROM completions
+             WHERE session_id = ?1 ORDER BY id",
+        )?;
+        let completions = stmt
+            .query_map([id], |row| {
+                Ok(CompletedGame {
+                    game_name: row.get(0)?,
+                    exe_name: row.get(1)?,
+                    completed_at: row.get(2)?,
+                    total_seconds: row.get(3)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(completions)
+    }
+
+    /// Per-game time in each of `ids`, for every game played in any of them.
+    pub fn compare(&self, ids: &[i64]) -> Result<Vec<GameComparison>> {
+        let mut games: Vec<GameComparison> = Vec::new();
+        for (i, id) in ids.iter().enumerate() {
+            for total in self.totals(*id)? {
+                let index = match games.iter().position(|g| g.game_name == total.game_name) {
+                    Some(index) => index,
+                    None => {
+                        games.push(GameComparison {
+                            game_name: total.game_name,
+                            seconds: vec![0; ids.len()],
+                        });
+                        games.len() - 1
+                    }
+                };
+                games[index].seconds[i] = total.total_seconds;
+            }
+        }
+
+        games.sort_by(|a, b| {
+            let total = |g: &GameComparison| g.seconds.iter().sum::<u64>();
+            total(b)
+                .cmp(&total(a))
+                .then_with(|| a.game_name.cmp(&b.game_name))
+        });
+        Ok(games)
+    }
+}
+
+fn session_from_row(row: &rusqlite::Row) -> rusqlite::Result<SessionInfo> {
+    let games: String = row.get(4)?;
+    let games = serde_json::from_str(&games).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(4, rusqlite::types::Type::Text, Box::new(e))
+    })?;
+    Ok(SessionInfo {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        started_at: row.get(2)?,
+        ended_at: row.get(3)?,
+        games,
+        stints: row.get(5)?,
+        total_seconds: row.get(6)?,
+    })
+}
@@ -0,0 +1,130 @@
+use anyhow::Result;
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// A swap interval with second precision.
+///
+/// Written to config as a human-readable string like `"45s"`, `"2m30s"` or
+/// `"1h"`; plain numbers are read as seconds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SwapDuration(u64);
+
+impl SwapDuration {
+    pub const fn from_secs(seconds: u64) -> Self {
+        Self(seconds)
+    }
+
+    pub const fn from_minutes(minutes: u64) -> Self {
+        Self(minutes * 60)
+    }
+
+    pub const fn as_secs(self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for SwapDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hours = self.0 / 3600;
+        let minutes = (self.0 % 3600) / 60;
+        let seconds = self.0 % 60;
+
+        if self.0 == 0 {
+            return write!(f, "0s");
+        }
+        if hours > 0 {
+            write!(f, "{}h", hours)?;
+        }
+        if minutes > 0 {
+            write!(f, "{}m", minutes)?;
+        }
+        if seconds > 0 {
+            write!(f, "{}s", seconds)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for SwapDuration {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let input = s.trim();
+        if input.is_empty() {
+            anyhow::bail!("Empty duration");
+        }
+        if let Ok(seconds) = input.parse::<u64>() {
+            return Ok(Self(seconds));
+        }
+
+        let mut total: u64 = 0;
+        let mut number = String::new();
+        for c in input.chars() {
+            if c.is_ascii_digit() {
+                number.push(c);
+                continue;
+            }
+            if c.is_whitespace() {
+                continue;
+            }
+
+            let unit = match c.to_ascii_lowercase() {
+                'h' => 3600,
+                'm' => 60,
+                's' => 1,
+                _ => anyhow::bail!("Invalid duration {:?}: unknown unit '{}'", s, c),
+            };
+            let value: u64 = number.parse().map_err(|_| {
+                anyhow::anyhow!("Invalid duration {:?}: missing number before '{}'", s, c)
+            })?;
+            total = value
+                .checked_mul(unit)
+                .and_then(|v| total.checked_add(v))
+                .ok_or_else(|| anyhow::anyhow!("Invalid duration {:?}: too large", s))?;
+            number.clear();
+        }
+
+        if !number.is_empty() {
+            anyhow::bail!("Invalid duration {:?}: missing unit after {}", s, number);
+        }
+        Ok(Self(total))
+    }
+}
+
+impl Serialize for SwapDuration {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for SwapDuration {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DurationVisitor;
+
+        impl Visitor<'_> for DurationVisitor {
+            type Value = SwapDuration;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a duration like \"2m30s\" or a number of seconds")
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                Ok(SwapDuration(v))
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+                u64::try_from(v)
+                    .map(SwapDuration)
+                    .map_err(|_| E::custom("duration must not be negative"))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                v.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(DurationVisitor)
+    }
+}
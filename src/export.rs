@@ -0,0 +1,156 @@
+use crate::state::{CompletedGame, SwapHistoryItem, SwapState};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serialize;
+
+/// Chapter line used when no template is configured.
+pub const DEFAULT_SUMMARY_TEMPLATE: &str = "{offset} {game}";
+
+/// Total play time of one game over the session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GameTotal {
+    pub game_name: String,
+    pub total_seconds: u64,
+    pub stints: usize,
+}
+
+/// Everything an export needs, taken from a `SwapState` at one moment.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionExport {
+    pub started_at: Option<DateTime<Utc>>,
+    pub exported_at: DateTime<Utc>,
+    pub swap_count: u64,
+    /// Seed of the session's delays and picks.
+    pub seed: Option<u64>,
+    /// Every stint, oldest first. A stint still in progress ends at `exported_at`.
+    pub history: Vec<SwapHistoryItem>,
+    /// Per-game totals, most played first.
+    pub totals: Vec<GameTotal>,
+    pub completed: Vec<CompletedGame>,
+}
+
+impl SessionExport {
+    pub fn new(state: &SwapState, now: DateTime<Utc>) -> Self {
+        let mut state = state.clone();
+        state.close_stint(now);
+
+        let mut totals: Vec<GameTotal> = state
+            .total_times
+            .iter()
+            .map(|(name, seconds)| GameTotal {
+                game_name: name.clone(),
+                total_seconds: *seconds,
+                stints: state
+                    .history
+                    .iter()
+                    .filter(|h| &h.game_name == name)
+                    .count(),
+            })
+            .collect();
+        totals.sort_by(|a, b| {
+            b.total_seconds
+                .cmp(&a.total_seconds)
+                .then_with(|| a.game_name.cmp(&b.game_name))
+        });
+
+        Self {
+            started_at: state.history.first().map(|h| h.started_at),
+            exported_at: now,
+            swap_count: state.swap_count,
+            seed: state.seed,
+            history: state.history,
+            totals,
+            completed: state.completed,
+        }
+    }
+
+    pub fn history_csv(&self) -> String {
+        let mut out = String::from(
+            "game_name,exe_name,started_at,ended_at,duration_seconds,paused_seconds,trigger\n",
+        );
+        for item in &self.history {
+            out.push_str(&csv_row(&[
+                &item.game_name,
+                &item.exe_name,
+                &item.started_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+                &item.ended_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+                &item.duration_seconds.to_string(),
+                &item.paused_seconds.to_string(),
+                item.trigger.as_str(),
+            ]));
+        }
+        out
+    }
+
+    pub fn totals_csv(&self) -> String {
+        let mut out = String::from("game_name,total_seconds,stints\n");
+        for total in &self.totals {
+            out.push_str(&csv_row(&[
+                &total.game_name,
+                &total.total_seconds.to_string(),
+                &total.stints.to_string(),
+            ]));
+        }
+        out
+    }
+
+    /// Renders `template` once per stint, e.g. a VOD chapter list with the
+    /// default `"{offset} {game}"`.
+    ///
+    /// Placeholders: `{index}`, `{offset}` (time since the session started),
+    /// `{game}`, `{exe}`, `{duration}`, `{paused}`, `{start}`, `{end}` and
+    /// `{trigger}`.
+    pub fn summary(&self, template: &str) -> String {
+        let Some(session_start) = self.started_at else {
+            return String::new();
+        };
+
+        let mut out = String::new();
+        for (i, item) in self.history.iter().enumerate() {
+            let offset = (item.started_at - session_start).num_seconds().max(0) as u64;
+            let line = template
+                .replace("{index}", &(i + 1).to_string())
+                .replace("{offset}", &format_offset(offset))
+                .replace("{game}", &item.game_name)
+                .replace("{exe}", &item.exe_name)
+                .replace("{duration}", &format_offset(item.duration_seconds))
+                .replace("{paused}", &format_offset(item.paused_seconds))
+                .replace("{start}", &item.started_at.format("%H:%M:%S").to_string())
+                .replace("{end}", &item.ended_at.format("%H:%M:%S").to_string())
+                .replace("{trigger}", item.trigger.as_str());
+            out.push_str(&line);
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// `M:SS` under an hour and `H:MM:SS` after, the format video chapters use.
+pub fn format_offset(seconds: u64) -> String {
+    let hours = seconds / 3600;
+    let minutes = (seconds % 3600) / 60;
+    let secs = seconds % 60;
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, secs)
+    } else {
+        format!("{}:{:02}", minutes, secs)
+    }
+}
+
+fn csv_row(fields: &[&str]) -> String {
+    let mut row = fields
+        .iter()
+        .map(|f| csv_field(f))
+        .collect::<Vec<_>>()
+        .join(",");
+    row.push('\n');
+    row
+}
+
+/// Quotes a field if it contains a separator, quote or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -0,0 +1,104 @@
+use super::InputBackend;
+use crate::keys::Key;
+use ::enigo::{Direction, Enigo, Keyboard, Settings};
+use anyhow::{Context, Result};
+use std::sync::Mutex;
+
+/// Sends keys through enigo (`SendInput` on Windows).
+pub struct EnigoInput {
+    enigo: Mutex<Enigo>,
+}
+
+impl EnigoInput {
+    pub fn new() -> Result<Self> {
+        let enigo = Enigo::new(&Settings::default()).context("Failed to set up keyboard input")?;
+        Ok(Self {
+            enigo: Mutex::new(enigo),
+        })
+    }
+
+    fn send(&self, key: Key, direction: Direction) -> Result<()> {
+        let key = enigo_key(key)?;
+        self.enigo.lock().unwrap().key(key, direction)?;
+        Ok(())
+    }
+}
+
+impl InputBackend for EnigoInput {
+    fn key_down(&self, key: Key) -> Result<()> {
+        self.send(key, Direction::Press)
+    }
+
+    fn key_up(&self, key: Key) -> Result<()> {
+        self.send(key, Direction::Release)
+    }
+
+    #[cfg(windows)]
+    fn can_post_keys(&self) -> bool {
+        true
+    }
+
+    #[cfg(windows)]
+    fn post_key_down(&self, window: isize, key: Key) -> Result<()> {
+        super::win32::post_key(window, key, true)
+    }
+
+    #[cfg(windows)]
+    fn post_key_up(&self, window: isize, key: Key) -> Result<()> {
+        super::win32::post_key(window, key, false)
+    }
+}
+
+fn enigo_key(key: Key) -> Result<::enigo::Key> {
+    use ::enigo::Key as E;
+    Ok(match key {
+        Key::Char(c) => E::Unicode(c),
+        Key::Escape => E::Escape,
+        Key::Enter => E::Return,
+        Key::Tab => E::Tab,
+        Key::Space => E::Space,
+        Key::Backspace => E::Backspace,
+        Key::Delete => E::Delete,
+        #[cfg(any(target_os = "windows", all(unix, not(target_os = "macos"))))]
+        Key::Insert => E::Insert,
+        Key::Home => E::Home,
+        Key::End => E::End,
+        Key::PageUp => E::PageUp,
+        Key::PageDown => E::PageDown,
+        Key::Up => E::UpArrow,
+        Key::Down => E::DownArrow,
+        Key::Left => E::LeftArrow,
+        Key::Right => E::RightArrow,
+        Key::Function(n) => match n {
+            1 => E::F1,
+            2 => E::F2,
+            3 => E::F3,
+            4 => E::F4,
+            5 => E::F5,
+            6 => E::F6,
+            7 => E::F7,
+            8 => E::F8,
+            9 => E::F9,
+            10 => E::F10,
+            11 => E::F11,
+            12 => E::F12,
+            13 => E::F13,
+            14 => E::F14,
+            15 => E::F15,
+            16 => E::F16,
+            17 => E::F17,
+            18 => E::F18,
+            19 => E::F19,
+            20 => E::F20,
+            _ => anyhow::bail!("No such key: F{}", n),
+        },
+        #[cfg(any(target_os = "windows", all(unix, not(target_os = "macos"))))]
+        Key::Pause => E::Pause,
+        Key::Ctrl => E::Control,
+        Key::Shift => E::Shift,
+        Key::Alt => E::Alt,
+        Key::Meta => E::Meta,
+        #[allow(unreachable_patterns)]
+        other => anyhow::bail!("{} can't be sent on this platform", other),
+    })
+}
@@ -0,0 +1,144 @@
+mod enigo;
+mod recording;
+#[cfg(windows)]
+mod win32;
+#[cfg(target_os = "linux")]
+mod xtest;
+
+use crate::clock::Clock;
+use crate::keys::{Key, KeySequence, KeyStep};
+use anyhow::Result;
+use std::sync::Arc;
+use tracing::info;
+
+pub use self::enigo::EnigoInput;
+pub use recording::{KeyEvent, RecordingInput};
+
+/// Keyboard injection, used both for the keys sent to games around a swap and
+/// for the keypress that lets us take the foreground on Windows.
+pub trait InputBackend: Send + Sync {
+    fn key_down(&self, key: Key) -> Result<()>;
+    fn key_up(&self, key: Key) -> Result<()>;
+
+    /// Presses and releases `key` right away.
+    fn tap(&self, key: Key) -> Result<()> {
+        self.key_down(key)?;
+        self.key_up(key)
+    }
+
+    /// Whether keys can be sent to a given window with `post_key_down` and
+    /// `post_key_up`.
+    fn can_post_keys(&self) -> bool {
+        false
+    }
+
+    /// Sends a key press straight to `window`, whether or not it has focus.
+    fn post_key_down(&self, window: isize, key: Key) -> Result<()> {
+        anyhow::bail!(
+            "Can't send {} to window {} with this input backend",
+            key,
+            window
+        )
+    }
+
+    fn post_key_up(&self, window: isize, key: Key) -> Result<()> {
+        anyhow::bail!(
+            "Can't send {} to window {} with this input backend",
+            key,
+            window
+        )
+    }
+}
+
+/// Sends keys to one window through `input`'s posted key events, so they
+/// reach it instead of whatever has focus. Use with `play`.
+pub struct WindowInput<'a> {
+    pub input: &'a dyn InputBackend,
+    pub window: isize,
+}
+
+impl InputBackend for WindowInput<'_> {
+    fn key_down(&self, key: Key) -> Result<()> {
+        self.input.post_key_down(self.window, key)
+    }
+
+    fn key_up(&self, key: Key) -> Result<()> {
+        self.input.post_key_up(self.window, key)
+    }
+}
+
+/// Sends `sequence`, waiting on `clock` for holds and pauses. Keys of a chord
+/// that went down are released even if a later one fails.
+pub async fn play(
+    input: &dyn InputBackend,
+    clock: &dyn Clock,
+    sequence: &KeySequence,
+) -> Result<()> {
+    for step in sequence.steps() {
+        match step {
+            KeyStep::Press { keys, hold } => {
+                let mut pressed = Vec::new();
+                let mut result = Ok(());
+                for key in keys {
+                    result = input.key_down(*key);
+                    if result.is_err() {
+                        break;
+                    }
+                    pressed.push(*key);
+                }
+                if result.is_ok() && !hold.is_zero() {
+                    clock.sleep(*hold).await;
+                }
+                for key in pressed.into_iter().rev() {
+                    let released = input.key_up(key);
+                    result = result.and(released);
+                }
+                result?;
+            }
+            KeyStep::Wait(duration) => clock.sleep(*duration).await,
+        }
+    }
+    Ok(())
+}
+
+/// Picks the input backend for the platform we were built for.
+pub fn default_backend() -> Result<Arc<dyn InputBackend>> {
+    #[cfg(target_os = "linux")]
+    {
+        Ok(Arc::new(xtest::XTestInput::connect()?))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        Ok(Arc::new(EnigoInput::new()?))
+    }
+}
+
+/// Input backend for `--simulate` runs, which only logs the keys it would send.
+pub struct SimulatedInput;
+
+impl InputBackend for SimulatedInput {
+    fn key_down(&self, key: Key) -> Result<()> {
+        info!("[simulate] {} down", key);
+        Ok(())
+    }
+
+    fn key_up(&self, key: Key) -> Result<()> {
+        info!("[simulate] {} up", key);
+        Ok(())
+    }
+
+    fn can_post_keys(&self) -> bool {
+        true
+    }
+
+    fn post_key_down(&self, window: isize, key: Key) -> Result<()> {
+        info!("[simulate] {} down in window {}", key, window);
+        Ok(())
+    }
+
+    fn post_key_up(&self, window: isize, key: Key) -> Result<()> {
+        info!("[simulate] {} up in window {}", key, window);
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+use super::InputBackend;
+use crate::keys::Key;
+use anyhow::Result;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+/// A key going down or up, as seen by a `RecordingInput`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyEvent {
+    Down(Key),
+    Up(Key),
+}
+
+impl fmt::Display for KeyEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyEvent::Down(key) => write!(f, "{} down", key),
+            KeyEvent::Up(key) => write!(f, "{} up", key),
+        }
+    }
+}
+
+/// Input backend that sends nothing and remembers every key event, so tests
+/// can check exactly what a swap would have typed.
+#[derive(Default)]
+pub struct RecordingInput {
+    events: Mutex<Vec<KeyEvent>>,
+    posted: Mutex<Vec<(isize, KeyEvent)>>,
+    reject_posted: AtomicBool,
+}
+
+impl RecordingInput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every key injected so far, oldest first.
+    pub fn events(&self) -> Vec<KeyEvent> {
+        self.events.lock().unwrap().clone()
+    }
+
+    /// Returns everything sent so far and forgets it.
+    pub fn take(&self) -> Vec<KeyEvent> {
+        std::mem::take(&mut *self.events.lock().unwrap())
+    }
+
+    /// Like `take`, for keys posted to a window, with the window they went to.
+    pub fn take_posted(&self) -> Vec<(isize, KeyEvent)> {
+        std::mem::take(&mut *self.posted.lock().unwrap())
+    }
+
+    /// Makes keys posted to a window fail, like a window that went away
+    /// between being found and getting its keys.
+    pub fn set_reject_posted(&self, reject: bool) {
+        self.reject_posted.store(reject, Ordering::SeqCst);
+    }
+}
+
+impl InputBackend for RecordingInput {
+    fn key_down(&self, key: Key) -> Result<()> {
+        self.events.lock().unwrap().push(KeyEvent::Down(key));
+        Ok(())
+    }
+
+    fn key_up(&self, key: Key) -> Result<()> {
+        self.events.lock().unwrap().push(KeyEvent::Up(key));
+        Ok(())
+    }
+
+    fn can_post_keys(&self) -> bool {
+        true
+    }
+
+    fn post_key_down(&self, window: isize, key: Key) -> Result<()> {
+        if self.reject_posted.load(Ordering::SeqCst) {
+            anyhow::bail!("Window {} rejected {}", window, key);
+        }
+        self.posted
+            .lock()
+            .unwrap()
+            .push((window, KeyEvent::Down(key)));
+        Ok(())
+    }
+
+    fn post_key_up(&self, window: isize, key: Key) -> Result<()> {
+        if self.reject_posted.load(Ordering::SeqCst) {
+            anyhow::bail!("Window {} rejected {}", window, key);
+        }
+        self.posted
+            .lock()
+            .unwrap()
+            .push((window, KeyEvent::Up(key)));
+        Ok(())
+    }
+}
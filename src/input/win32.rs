@@ -0,0 +1,78 @@
+use crate::keys::Key;
+use anyhow::{Context, Result};
+use windows::Win32::Foundation::{HWND, LPARAM, WPARAM};
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    MapVirtualKeyW, VkKeyScanW, MAPVK_VK_TO_VSC, VIRTUAL_KEY, VK_BACK, VK_CONTROL, VK_DELETE,
+    VK_DOWN, VK_END, VK_ESCAPE, VK_F1, VK_HOME, VK_INSERT, VK_LEFT, VK_LWIN, VK_MENU, VK_NEXT,
+    VK_PAUSE, VK_PRIOR, VK_RETURN, VK_RIGHT, VK_SHIFT, VK_SPACE, VK_TAB, VK_UP,
+};
+use windows::Win32::UI::WindowsAndMessaging::{
+    PostMessageW, WM_KEYDOWN, WM_KEYUP, WM_SYSKEYDOWN, WM_SYSKEYUP,
+};
+
+/// Posts a key message to `window`'s message queue. The game sees the key
+/// even when it's in the background, but modifiers held this way don't
+/// change the keyboard state, so chords may not register.
+pub fn post_key(window: isize, key: Key, down: bool) -> Result<()> {
+    let vk = virtual_key(key)?;
+    let message = match (key, down) {
+        (Key::Alt, true) => WM_SYSKEYDOWN,
+        (Key::Alt, false) => WM_SYSKEYUP,
+        (_, true) => WM_KEYDOWN,
+        (_, false) => WM_KEYUP,
+    };
+
+    unsafe {
+        // Repeat count 1 and the scan code; key-up also sets the previous
+        // state and transition bits
+        let scan_code = MapVirtualKeyW(vk.0 as u32, MAPVK_VK_TO_VSC);
+        let mut bits: u32 = 1 | (scan_code & 0xff) << 16;
+        if !down {
+            bits |= 0xc000_0000;
+        }
+
+        PostMessageW(
+            HWND(window),
+            message,
+            WPARAM(vk.0 as usize),
+            LPARAM(bits as isize),
+        )
+        .with_context(|| format!("Failed to post {} to window {}", key, window))
+    }
+}
+
+fn virtual_key(key: Key) -> Result<VIRTUAL_KEY> {
+    Ok(match key {
+        Key::Char(c) => {
+            let unit = u16::try_from(c as u32)
+                .ok()
+                .with_context(|| format!("{} has no virtual key", key))?;
+            let scan = unsafe { VkKeyScanW(unit) };
+            if scan == -1 {
+                anyhow::bail!("{} isn't on the current keyboard layout", key);
+            }
+            VIRTUAL_KEY((scan as u16) & 0xff)
+        }
+        Key::Escape => VK_ESCAPE,
+        Key::Enter => VK_RETURN,
+        Key::Tab => VK_TAB,
+        Key::Space => VK_SPACE,
+        Key::Backspace => VK_BACK,
+        Key::Delete => VK_DELETE,
+        Key::Insert => VK_INSERT,
+        Key::Home => VK_HOME,
+        Key::End => VK_END,
+        Key::PageUp => VK_PRIOR,
+        Key::PageDown => VK_NEXT,
+        Key::Up => VK_UP,
+        Key::Down => VK_DOWN,
+        Key::Left => VK_LEFT,
+        Key::Right => VK_RIGHT,
+        Key::Function(n) => VIRTUAL_KEY(VK_F1.0 + u16::from(n.saturating_sub(1))),
+        Key::Pause => VK_PAUSE,
+        Key::Ctrl => VK_CONTROL,
+        Key::Shift => VK_SHIFT,
+        Key::Alt => VK_MENU,
+        Key::Meta => VK_LWIN,
+    })
+}
@@ -0,0 +1,225 @@
+use super::InputBackend;
+use crate::keys::Key;
+use anyhow::{Context, Result};
+use std::sync::Mutex;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{
+    ConnectionExt as _, EventMask, KeyButMask, KeyPressEvent, Keycode, Keysym, Window,
+    KEY_PRESS_EVENT, KEY_RELEASE_EVENT,
+};
+use x11rb::protocol::xtest::ConnectionExt as _;
+use x11rb::rust_connection::RustConnection;
+use x11rb::CURRENT_TIME;
+
+const XK_SHIFT_L: Keysym = 0xffe1;
+
+/// Sends keys as fake X input through the XTEST extension, which games
+/// running under X11 or XWayland see as real key presses.
+pub struct XTestInput {
+    conn: RustConnection,
+    root: Window,
+    keymap: Keymap,
+    /// Keys pressed together with Shift, so the same Shift is released after.
+    shifted: Mutex<Vec<Key>>,
+    /// Modifiers held down in posted events, which carry their own state.
+    posted_modifiers: Mutex<KeyButMask>,
+}
+
+/// The keyboard mapping read at startup: `keysyms_per_keycode` keysyms for
+/// each keycode from `min_keycode` on.
+struct Keymap {
+    min_keycode: Keycode,
+    keysyms_per_keycode: usize,
+    keysyms: Vec<Keysym>,
+}
+
+impl Keymap {
+    /// The keycode typing `keysym`, and whether Shift has to be held for it.
+    fn lookup(&self, keysym: Keysym) -> Option<(Keycode, bool)> {
+        let per_keycode = self.keysyms_per_keycode.max(1);
+        self.keysyms
+            .chunks(per_keycode)
+            .enumerate()
+            .find_map(|(i, syms)| {
+                // Only the unshifted and shifted levels of the first group
+                let level = syms.iter().take(2).position(|&s| s == keysym)?;
+                let keycode = Keycode::try_from(self.min_keycode as usize + i).ok()?;
+                Some((keycode, level == 1))
+            })
+    }
+}
+
+impl XTestInput {
+    pub fn connect() -> Result<Self> {
+        let (conn, screen_num) =
+            x11rb::connect(None).context("Failed to connect to the X server")?;
+        let root = conn.setup().roots[screen_num].root;
+
+        conn.xtest_get_version(2, 2)?
+            .reply()
+            .context("The X server doesn't support the XTEST extension")?;
+
+        let setup = conn.setup();
+        let (min_keycode, max_keycode) = (setup.min_keycode, setup.max_keycode);
+        let mapping = conn
+            .get_keyboard_mapping(min_keycode, max_keycode - min_keycode + 1)?
+            .reply()
+            .context("Failed to read the keyboard mapping")?;
+
+        Ok(Self {
+            conn,
+            root,
+            keymap: Keymap {
+                min_keycode,
+                keysyms_per_keycode: mapping.keysyms_per_keycode as usize,
+                keysyms: mapping.keysyms,
+            },
+            shifted: Mutex::new(Vec::new()),
+            posted_modifiers: Mutex::new(KeyButMask::default()),
+        })
+    }
+
+    fn keycode(&self, keysym: Keysym, key: Key) -> Result<(Keycode, bool)> {
+        self.keymap
+            .lookup(keysym)
+            .with_context(|| format!("{} isn't on the current keyboard layout", key))
+    }
+
+    fn fake(&self, event: u8, keycode: Keycode) -> Result<()> {
+        self.conn
+            .xtest_fake_input(event, keycode, CURRENT_TIME, self.root, 0, 0, 0)?;
+        Ok(())
+    }
+
+    /// Sends a key event to `window` with `SendEvent`. Clients can tell these
+    /// apart from real input, and some games ignore them.
+    fn post(&self, window: isize, key: Key, down: bool) -> Result<()> {
+        let (keycode, needs_shift) = self.keycode(keysym(key), key)?;
+
+        let mut modifiers = self.posted_modifiers.lock().unwrap();
+        let mut state = *modifiers;
+        if needs_shift {
+            state |= KeyButMask::SHIFT;
+        }
+        if let Some(modifier) = modifier_mask(key) {
+            if down {
+                *modifiers |= modifier;
+            } else {
+                *modifiers = KeyButMask::from(u16::from(*modifiers) & !u16::from(modifier));
+            }
+        }
+
+        let event = KeyPressEvent {
+            response_type: if down {
+                KEY_PRESS_EVENT
+            } else {
+                KEY_RELEASE_EVENT
+            },
+            detail: keycode,
+            sequence: 0,
+            time: CURRENT_TIME,
+            root: self.root,
+            event: window as Window,
+            child: x11rb::NONE,
+            root_x: 0,
+            root_y: 0,
+            event_x: 0,
+            event_y: 0,
+            state,
+            same_screen: true,
+        };
+        let mask = if down {
+            EventMask::KEY_PRESS
+        } else {
+            EventMask::KEY_RELEASE
+        };
+        self.conn
+            .send_event(false, window as Window, mask, event)
+            .with_context(|| format!("Failed to send {} to window {}", key, window))?;
+        self.conn.flush()?;
+        Ok(())
+    }
+}
+
+impl InputBackend for XTestInput {
+    fn key_down(&self, key: Key) -> Result<()> {
+        let (keycode, needs_shift) = self.keycode(keysym(key), key)?;
+        if needs_shift {
+            let (shift, _) = self.keycode(XK_SHIFT_L, Key::Shift)?;
+            self.fake(KEY_PRESS_EVENT, shift)?;
+            self.shifted.lock().unwrap().push(key);
+        }
+        self.fake(KEY_PRESS_EVENT, keycode)?;
+        self.conn.flush()?;
+        Ok(())
+    }
+
+    fn key_up(&self, key: Key) -> Result<()> {
+        let (keycode, _) = self.keycode(keysym(key), key)?;
+        self.fake(KEY_RELEASE_EVENT, keycode)?;
+
+        let mut shifted = self.shifted.lock().unwrap();
+        if let Some(i) = shifted.iter().position(|&k| k == key) {
+            shifted.remove(i);
+            let (shift, _) = self.keycode(XK_SHIFT_L, Key::Shift)?;
+            self.fake(KEY_RELEASE_EVENT, shift)?;
+        }
+        self.conn.flush()?;
+        Ok(())
+    }
+
+    fn can_post_keys(&self) -> bool {
+        true
+    }
+
+    fn post_key_down(&self, window: isize, key: Key) -> Result<()> {
+        self.post(window, key, true)
+    }
+
+    fn post_key_up(&self, window: isize, key: Key) -> Result<()> {
+        self.post(window, key, false)
+    }
+}
+
+fn modifier_mask(key: Key) -> Option<KeyButMask> {
+    match key {
+        Key::Shift => Some(KeyButMask::SHIFT),
+        Key::Ctrl => Some(KeyButMask::CONTROL),
+        Key::Alt => Some(KeyButMask::MOD1),
+        Key::Meta => Some(KeyButMask::MOD4),
+        _ => None,
+    }
+}
+
+/// The X keysym for `key`, from `X11/keysymdef.h`.
+fn keysym(key: Key) -> Keysym {
+    match key {
+        // Latin-1 keysyms match their code points, the rest of Unicode is
+        // offset by 0x01000000
+        Key::Char(c) => match c as u32 {
+            cp @ (0x20..=0x7e | 0xa0..=0xff) => cp,
+            cp => 0x0100_0000 + cp,
+        },
+        Key::Escape => 0xff1b,
+        Key::Enter => 0xff0d,
+        Key::Tab => 0xff09,
+        Key::Space => 0x0020,
+        Key::Backspace => 0xff08,
+        Key::Delete => 0xffff,
+        Key::Insert => 0xff63,
+        Key::Home => 0xff50,
+        Key::End => 0xff57,
+        Key::PageUp => 0xff55,
+        Key::PageDown => 0xff56,
+        Key::Up => 0xff52,
+        Key::Down => 0xff54,
+        Key::Left => 0xff51,
+        Key::Right => 0xff53,
+        Key::Function(n) => 0xffbe + u32::from(n.saturating_sub(1)),
+        Key::Pause => 0xff13,
+        Key::Ctrl => 0xffe3,
+        Key::Shift => XK_SHIFT_L,
+        Key::Alt => 0xffe9,
+        Key::Meta => 0xffeb,
+    }
+}
@@ -0,0 +1,285 @@
+use anyhow::Result;
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// A key that can be sent to a game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    /// A letter, digit or symbol key, stored lowercase.
+    Char(char),
+    Escape,
+    Enter,
+    Tab,
+    Space,
+    Backspace,
+    Delete,
+    Insert,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Up,
+    Down,
+    Left,
+    Right,
+    /// F1 to F20.
+    Function(u8),
+    Pause,
+    Ctrl,
+    Shift,
+    Alt,
+    /// The Windows / Super / Command key.
+    Meta,
+}
+
+const NAMED_KEYS: &[(&str, Key)] = &[
+    ("Escape", Key::Escape),
+    ("Enter", Key::Enter),
+    ("Tab", Key::Tab),
+    ("Space", Key::Space),
+    ("Backspace", Key::Backspace),
+    ("Delete", Key::Delete),
+    ("Insert", Key::Insert),
+    ("Home", Key::Home),
+    ("End", Key::End),
+    ("PageUp", Key::PageUp),
+    ("PageDown", Key::PageDown),
+    ("Up", Key::Up),
+    ("Down", Key::Down),
+    ("Left", Key::Left),
+    ("Right", Key::Right),
+    ("Pause", Key::Pause),
+    ("Ctrl", Key::Ctrl),
+    ("Shift", Key::Shift),
+    ("Alt", Key::Alt),
+    ("Meta", Key::Meta),
+];
+
+const KEY_ALIASES: &[(&str, Key)] = &[
+    ("esc", Key::Escape),
+    ("return", Key::Enter),
+    ("del", Key::Delete),
+    ("ins", Key::Insert),
+    ("pgup", Key::PageUp),
+    ("pgdn", Key::PageDown),
+    ("control", Key::Ctrl),
+    ("win", Key::Meta),
+    ("super", Key::Meta),
+    ("cmd", Key::Meta),
+];
+
+/// Characters that separate the parts of a sequence, so they go by name.
+const CHAR_NAMES: &[(&str, char)] = &[("Comma", ','), ("Plus", '+'), ("Colon", ':')];
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Key::Char(c) => match CHAR_NAMES.iter().find(|(_, ch)| ch == c) {
+                Some((name, _)) => write!(f, "{}", name),
+                None => write!(f, "{}", c.to_ascii_uppercase()),
+            },
+            Key::Function(n) => write!(f, "F{}", n),
+            key => {
+                let name = NAMED_KEYS
+                    .iter()
+                    .find(|(_, k)| k == key)
+                    .map(|(name, _)| *name)
+                    .unwrap_or("?");
+                write!(f, "{}", name)
+            }
+        }
+    }
+}
+
+impl FromStr for Key {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let name = s.trim();
+        let mut chars = name.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            if CHAR_NAMES.iter().all(|(_, ch)| *ch != c) {
+                return Ok(Key::Char(c.to_ascii_lowercase()));
+            }
+        }
+        if let Some((_, c)) = CHAR_NAMES
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        {
+            return Ok(Key::Char(*c));
+        }
+
+        let found = NAMED_KEYS
+            .iter()
+            .chain(KEY_ALIASES)
+            .find(|(n, _)| n.eq_ignore_ascii_case(name));
+        if let Some((_, key)) = found {
+            return Ok(*key);
+        }
+        if let Some(n) = name
+            .strip_prefix(['f', 'F'])
+            .and_then(|n| n.parse::<u8>().ok())
+            .filter(|n| (1..=20).contains(n))
+        {
+            return Ok(Key::Function(n));
+        }
+        anyhow::bail!("Unknown key {:?}", name)
+    }
+}
+
+/// One step of a key sequence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyStep {
+    /// Presses `keys` in order, holds them for `hold`, and releases them in
+    /// reverse, e.g. `Ctrl+Shift+P`.
+    Press {
+        keys: Vec<Key>,
+        hold: Duration,
+    },
+    Wait(Duration),
+}
+
+/// Keys sent to a game when swapping to or from it, e.g. to pause it.
+///
+/// Written to config as steps separated by commas: a key or a `+`-joined
+/// chord, optionally held with `:500ms`, or a pause like `wait 200ms`.
+/// For example `"Ctrl+P"`, `"Escape, wait 200ms, Enter:100ms"`. An empty
+/// string sends nothing. `,`, `+` and `:` are written `Comma`, `Plus` and
+/// `Colon`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeySequence(Vec<KeyStep>);
+
+impl KeySequence {
+    pub fn new(steps: Vec<KeyStep>) -> Self {
+        Self(steps)
+    }
+
+    /// A single press of Escape, the pause key of most games.
+    pub fn escape() -> Self {
+        Self(vec![KeyStep::Press {
+            keys: vec![Key::Escape],
+            hold: Duration::ZERO,
+        }])
+    }
+
+    pub fn steps(&self) -> &[KeyStep] {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Display for KeySequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, step) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            match step {
+                KeyStep::Press { keys, hold } => {
+                    for (j, key) in keys.iter().enumerate() {
+                        if j > 0 {
+                            write!(f, "+")?;
+                        }
+                        write!(f, "{}", key)?;
+                    }
+                    if !hold.is_zero() {
+                        write!(f, ":{}", format_millis(*hold))?;
+                    }
+                }
+                KeyStep::Wait(duration) => write!(f, "wait {}", format_millis(*duration))?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for KeySequence {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if s.trim().is_empty() {
+            return Ok(Self::default());
+        }
+
+        let mut steps = Vec::new();
+        for step in s.split(',') {
+            let step = step.trim();
+            let (command, rest) = step.split_once(' ').unwrap_or((step, ""));
+            if command.eq_ignore_ascii_case("wait") {
+                steps.push(KeyStep::Wait(parse_millis(rest)?));
+                continue;
+            }
+
+            let (chord, hold) = match step.split_once(':') {
+                Some((chord, hold)) => (chord, parse_millis(hold)?),
+                None => (step, Duration::ZERO),
+            };
+            let keys = chord
+                .split('+')
+                .map(str::parse)
+                .collect::<Result<Vec<Key>>>()?;
+            steps.push(KeyStep::Press { keys, hold });
+        }
+        Ok(Self(steps))
+    }
+}
+
+/// Reads `500ms`, `2s` or `1.5s`.
+fn parse_millis(s: &str) -> Result<Duration> {
+    let input = s.trim();
+    let millis = if let Some(ms) = input.strip_suffix("ms") {
+        ms.trim().parse::<u64>().ok()
+    } else if let Some(secs) = input.strip_suffix('s') {
+        secs.trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|s| s.is_finite() && *s >= 0.0)
+            .map(|s| (s * 1000.0).round() as u64)
+    } else {
+        None
+    };
+    millis
+        .map(Duration::from_millis)
+        .ok_or_else(|| anyhow::anyhow!("Invalid delay {:?}, use e.g. 200ms or 1s", input))
+}
+
+fn format_millis(duration: Duration) -> String {
+    let millis = duration.as_millis();
+    if millis.is_multiple_of(1000) {
+        format!("{}s", millis / 1000)
+    } else {
+        format!("{}ms", millis)
+    }
+}
+
+impl Serialize for KeySequence {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for KeySequence {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SequenceVisitor;
+
+        impl Visitor<'_> for SequenceVisitor {
+            type Value = KeySequence;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a key sequence like \"Escape\" or \"Ctrl+P, wait 200ms\"")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<KeySequence, E> {
+                v.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(SequenceVisitor)
+    }
+}
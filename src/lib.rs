@@ -0,0 +1,18 @@
+pub mod clock;
+pub mod config;
+pub mod db;
+pub mod duration;
+pub mod export;
+pub mod input;
+pub mod keys;
+pub mod obs;
+pub mod process;
+pub mod rng;
+pub mod script;
+pub mod selection;
+pub mod server;
+pub mod session;
+pub mod state;
+pub mod stats;
+pub mod swapper;
+pub mod windows;
@@ -1,36 +1,247 @@
-mod config;
-mod input;
-mod obs;
-mod server;
-mod state;
-mod swapper;
-mod windows;
-
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::Arc;
 
-use config::ConfigManager;
-use state::AppState;
-use swapper::Swapper;
-use tracing::info;
+use anyhow::Context;
+use clap::{Parser, Subcommand, ValueEnum};
+use donaldswap::config::{AppConfig, ConfigManager};
+use donaldswap::db::SessionDb;
+use donaldswap::export::SessionExport;
+use donaldswap::input::{self, InputBackend, SimulatedInput};
+use donaldswap::process::{self, MockProcessBackend, ProcessBackend, Suspender};
+use donaldswap::script::{ScriptEnd, SwapScript};
+use donaldswap::server;
+use donaldswap::session::{self, SessionStore};
+use donaldswap::state::AppState;
+use donaldswap::swapper::Swapper;
+use donaldswap::windows::{self, MockWindowBackend, WindowBackend};
+use tracing::{info, warn};
+
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Run headless against fake windows loaded from this TOML file
+    #[arg(long, value_name = "FILE")]
+    simulate: Option<PathBuf>,
+
+    /// Pick up the previous session where it left off, timer included
+    #[arg(long, conflicts_with = "fresh")]
+    resume: bool,
+
+    /// Start a new session without offering to resume the previous one
+    #[arg(long)]
+    fresh: bool,
+
+    /// Name the session is recorded under; defaults to the date and time it started
+    #[arg(long, value_name = "NAME")]
+    session_name: Option<String>,
+
+    /// Play the swaps in this TOML or CSV file as written instead of picking at random
+    #[arg(long, value_name = "FILE")]
+    script: Option<PathBuf>,
+
+    /// Start the script over after its last swap instead of ending the session
+    #[arg(long, requires = "script")]
+    loop_script: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Export the saved session's history and per-game totals
+    Export {
+        #[arg(long, value_enum, default_value_t = ExportFormat::Json)]
+        format: ExportFormat,
+
+        /// Line rendered per stint for `--format summary`; defaults to
+        /// `summary_template` from the config
+        #[arg(long)]
+        template: Option<String>,
+
+        /// Write to this file instead of stdout
+        #[arg(long, short, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    HistoryCsv,
+    TotalsCsv,
+    Json,
+    Summary,
+}
+
+fn export(
+    format: ExportFormat,
+    template: Option<String>,
+    output: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    let dir = std::env::current_dir()?;
+    let store = SessionStore::new(dir.join("session.json"));
+    let snapshot = store
+        .load()?
+        .with_context(|| format!("No saved session found at {:?}", store.path()))?;
+    let export = SessionExport::new(&snapshot.state, snapshot.saved_at);
+
+    let content = match format {
+        ExportFormat::HistoryCsv => export.history_csv(),
+        ExportFormat::TotalsCsv => export.totals_csv(),
+        ExportFormat::Json => serde_json::to_string_pretty(&export)?,
+        ExportFormat::Summary => {
+            let config_path = dir.join("config.toml");
+            let template = match template {
+                Some(template) => template,
+                None if config_path.exists() => AppConfig::load(&config_path)?.summary_template,
+                None => AppConfig::default().summary_template,
+            };
+            export.summary(&template)
+        }
+    };
+
+    match output {
+        Some(path) => std::fs::write(&path, content)
+            .with_context(|| format!("Failed to write export: {:?}", path)),
+        None => {
+            print!("{}", content);
+            Ok(())
+        }
+    }
+}
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     tracing_subscriber::fmt::init();
+    let cli = Cli::parse();
+
+    if let Some(Command::Export {
+        format,
+        template,
+        output,
+    }) = cli.command
+    {
+        return export(format, template, output);
+    }
 
     let config_path = std::env::current_dir()?.join("config.toml");
     let config_manager = Arc::new(ConfigManager::new(config_path)?);
+
+    let script = match &cli.script {
+        Some(path) => {
+            let mut script = SwapScript::load(path, &config_manager.get().await)?;
+            if cli.loop_script {
+                script = script.with_end(ScriptEnd::Loop);
+            }
+            info!(
+                "Playing {} scripted swaps from {:?}",
+                script.entries.len(),
+                path
+            );
+            Some(script)
+        }
+        None => None,
+    };
     let app_state = Arc::new(AppState::new());
-    let swapper = Arc::new(Swapper::new(
+
+    let session_store = Arc::new(SessionStore::new(
+        std::env::current_dir()?.join("session.json"),
+    ));
+    let db = Arc::new(SessionDb::open(
+        &std::env::current_dir()?.join("donaldswap.db"),
+    )?);
+    let previous = match session_store.load() {
+        Ok(previous) => previous,
+        Err(e) => {
+            warn!("Ignoring previous session: {:?}", e);
+            None
+        }
+    };
+    match previous {
+        Some(snapshot) if cli.resume => {
+            info!("Resuming session saved at {}", snapshot.saved_at);
+            let state = snapshot.into_state(app_state.clock.now());
+            app_state.update_state(|s| *s = state).await;
+        }
+        Some(snapshot) if cli.fresh => {
+            info!("Starting a fresh session");
+            // The previous one won't be resumed, so it ended when it was last saved
+            if let Err(e) = db.finish(&snapshot.state, snapshot.saved_at) {
+                warn!("Failed to close the previous session: {:?}", e);
+            }
+        }
+        Some(snapshot) => {
+            info!("Previous session found, it can be resumed from the dashboard");
+            session_store.set_previous(Some(snapshot));
+        }
+        None if cli.resume => warn!("No previous session to resume, starting fresh"),
+        None => {}
+    }
+
+    if app_state.get_state().await.session_id.is_none() {
+        let name = cli.session_name.unwrap_or_else(|| {
+            format!("Session {}", chrono::Local::now().format("%Y-%m-%d %H:%M"))
+        });
+        let config = config_manager.get().await;
+        let id = db.create_session(&name, app_state.clock.now(), &config)?;
+        info!("Recording session {:?}", name);
+        app_state
+            .update_state(|s| {
+                s.session_id = Some(id);
+                s.session_name = Some(name);
+            })
+            .await;
+    }
+
+    let simulation = match &cli.simulate {
+        Some(path) => Some(Arc::new(MockWindowBackend::load(path)?)),
+        None => None,
+    };
+    let (window_backend, input_backend): (Arc<dyn WindowBackend>, Arc<dyn InputBackend>) =
+        match &simulation {
+            Some(mock) => {
+                info!(
+                    "Simulation mode: using fake windows from {:?}",
+                    cli.simulate
+                );
+                (mock.clone(), Arc::new(SimulatedInput))
+            }
+            None => {
+                let input = input::default_backend()?;
+                (windows::default_backend(input.clone())?, input)
+            }
+        };
+    let process_backend: Arc<dyn ProcessBackend> = match &simulation {
+        Some(_) => Arc::new(MockProcessBackend::new()),
+        None => process::default_backend()?,
+    };
+    let suspender = Arc::new(Suspender::new(process_backend));
+
+    let mut swapper = Swapper::new(
         config_manager.config(),
         app_state.clone(),
-    ));
+        window_backend.clone(),
+        input_backend,
+    )
+    .with_session_db(db.clone())
+    .with_suspender(suspender.clone());
+    if let Some(script) = script {
+        swapper = swapper.with_script(script);
+    }
+    let swapper = Arc::new(swapper);
 
     let swapper_clone = swapper.clone();
     tokio::spawn(async move {
         swapper_clone.run().await;
     });
 
+    tokio::spawn(session::autosave(
+        session_store.clone(),
+        app_state.clone(),
+        std::time::Duration::from_secs(10),
+    ));
+
     let app_state_clone = app_state.clone();
     tokio::spawn(async move {
         let mut interval = tokio::time::interval(std::time::Duration::from_secs(1));
@@ -45,6 +256,10 @@ async fn main() -> anyhow::Result<()> {
         config_manager.clone(),
         app_state.clone(),
         swapper.clone(),
+        session_store.clone(),
+        db.clone(),
+        window_backend.clone(),
+        simulation,
     );
 
     let addr: SocketAddr = "127.0.0.1:3000".parse()?;
@@ -55,7 +270,77 @@ async fn main() -> anyhow::Result<()> {
     info!("===============================================");
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    // Not a graceful shutdown: open dashboard websockets would hold it up
+    tokio::select! {
+        result = axum::serve(listener, app) => result?,
+        _ = shutdown_signal() => {}
+    }
+
+    // Suspended games would stay frozen after we exit
+    suspender.resume_all();
+
+    // Record the sessions as ended where they stopped; resuming one reopens it
+    let state = app_state.get_state().await;
+    let now = app_state.clock.now();
+    let previous = session_store.take_previous();
+    let result = tokio::task::spawn_blocking(move || {
+        db.finish(&state, now)?;
+        match previous {
+            Some(previous) => db.finish(&previous.state, previous.saved_at),
+            None => Ok(()),
+        }
+    })
+    .await;
+    if let Err(e) = result.map_err(anyhow::Error::from).and_then(|r| r) {
+        warn!("Failed to record the end of the session: {:?}", e);
+    }
+    info!("Shut down");
 
     Ok(())
 }
+
+/// Resolves on Ctrl+C, or when the terminal or service manager asks us to stop.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!("Failed to listen for Ctrl+C: {:?}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                warn!("Failed to listen for SIGTERM: {:?}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(windows)]
+    let terminate = async {
+        match tokio::signal::windows::ctrl_close() {
+            Ok(mut close) => {
+                close.recv().await;
+            }
+            Err(e) => {
+                warn!("Failed to listen for the console closing: {:?}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(any(unix, windows)))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+    info!("Shutting down, resuming suspended games");
+}
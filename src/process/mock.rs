@@ -0,0 +1,36 @@
+use super::ProcessBackend;
+use anyhow::Result;
+use std::collections::BTreeSet;
+use std::sync::Mutex;
+use tracing::info;
+
+/// Process backend for `--simulate` runs and tests, which only keeps track
+/// of which pids would be suspended.
+#[derive(Default)]
+pub struct MockProcessBackend {
+    suspended: Mutex<BTreeSet<u32>>,
+}
+
+impl MockProcessBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn suspended(&self) -> Vec<u32> {
+        self.suspended.lock().unwrap().iter().copied().collect()
+    }
+}
+
+impl ProcessBackend for MockProcessBackend {
+    fn suspend(&self, pid: u32) -> Result<()> {
+        info!("[simulate] suspend pid {}", pid);
+        self.suspended.lock().unwrap().insert(pid);
+        Ok(())
+    }
+
+    fn resume(&self, pid: u32) -> Result<()> {
+        info!("[simulate] resume pid {}", pid);
+        self.suspended.lock().unwrap().remove(&pid);
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+mod mock;
+#[cfg(unix)]
+mod unix;
+#[cfg(windows)]
+mod win32;
+
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use tracing::{info, warn};
+
+pub use mock::MockProcessBackend;
+
+/// Freezes and thaws whole processes, for games paused by suspending them.
+pub trait ProcessBackend: Send + Sync {
+    fn suspend(&self, pid: u32) -> Result<()>;
+    fn resume(&self, pid: u32) -> Result<()>;
+}
+
+/// Picks the process backend for the platform we were built for.
+pub fn default_backend() -> Result<Arc<dyn ProcessBackend>> {
+    #[cfg(unix)]
+    {
+        Ok(Arc::new(unix::SignalBackend))
+    }
+
+    #[cfg(windows)]
+    {
+        Ok(Arc::new(win32::ThreadBackend))
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    {
+        anyhow::bail!("Suspending processes isn't supported on this platform")
+    }
+}
+
+/// OBS executables. Freezing OBS would freeze the stream.
+const PROTECTED_EXES: &[&str] = &["obs64.exe", "obs32.exe", "obs.exe", "obs"];
+
+/// Suspends game processes through a `ProcessBackend`, refusing the ones
+/// that must keep running, and remembers what it suspended so everything can
+/// be resumed on shutdown.
+pub struct Suspender {
+    backend: Arc<dyn ProcessBackend>,
+    /// Suspended processes by pid, with their exe names.
+    suspended: Mutex<BTreeMap<u32, String>>,
+}
+
+impl Suspender {
+    pub fn new(backend: Arc<dyn ProcessBackend>) -> Self {
+        Self {
+            backend,
+            suspended: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn suspend(&self, pid: u32, exe_name: &str) -> Result<()> {
+        if pid == 0 {
+            anyhow::bail!("Don't know which process {} is", exe_name);
+        }
+        if pid == std::process::id() {
+            anyhow::bail!("Refusing to suspend DonaldSwap itself");
+        }
+        if PROTECTED_EXES
+            .iter()
+            .any(|exe| exe.eq_ignore_ascii_case(exe_name))
+        {
+            anyhow::bail!("Refusing to suspend OBS ({})", exe_name);
+        }
+
+        let mut suspended = self.suspended.lock().unwrap();
+        if suspended.contains_key(&pid) {
+            return Ok(());
+        }
+        self.backend.suspend(pid)?;
+        suspended.insert(pid, exe_name.to_string());
+        info!("Suspended {} (pid {})", exe_name, pid);
+        Ok(())
+    }
+
+    /// Resumes `pid` if we suspended it.
+    pub fn resume(&self, pid: u32) -> Result<()> {
+        let mut suspended = self.suspended.lock().unwrap();
+        let Some(exe_name) = suspended.get(&pid).cloned() else {
+            return Ok(());
+        };
+        self.backend.resume(pid)?;
+        suspended.remove(&pid);
+        info!("Resumed {} (pid {})", exe_name, pid);
+        Ok(())
+    }
+
+    /// Resumes every process of `exe_name` we suspended.
+    pub fn resume_exe(&self, exe_name: &str) -> Result<()> {
+        let pids: Vec<u32> = self
+            .suspended()
+            .into_iter()
+            .filter(|(_, exe)| exe.eq_ignore_ascii_case(exe_name))
+            .map(|(pid, _)| pid)
+            .collect();
+        pids.into_iter().try_for_each(|pid| self.resume(pid))
+    }
+
+    pub fn is_suspended(&self, pid: u32) -> bool {
+        self.suspended.lock().unwrap().contains_key(&pid)
+    }
+
+    /// The processes currently suspended, as pid and exe name.
+    pub fn suspended(&self) -> Vec<(u32, String)> {
+        let suspended = self.suspended.lock().unwrap();
+        suspended
+            .iter()
+            .map(|(pid, exe)| (*pid, exe.clone()))
+            .collect()
+    }
+
+    /// Resumes everything we suspended, e.g. on shutdown.
+    pub fn resume_all(&self) {
+        for (pid, exe_name) in self.suspended() {
+            if let Err(e) = self.resume(pid) {
+                warn!("Failed to resume {} (pid {}): {:?}", exe_name, pid, e);
+            }
+        }
+    }
+}
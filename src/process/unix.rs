@@ -0,0 +1,26 @@
+use super::ProcessBackend;
+use anyhow::{Context, Result};
+
+/// Suspends processes with SIGSTOP and resumes them with SIGCONT.
+pub struct SignalBackend;
+
+impl SignalBackend {
+    fn signal(pid: u32, signal: libc::c_int) -> Result<()> {
+        let pid = libc::pid_t::try_from(pid).context("Invalid pid")?;
+        if unsafe { libc::kill(pid, signal) } != 0 {
+            return Err(std::io::Error::last_os_error())
+                .with_context(|| format!("Failed to signal process {}", pid));
+        }
+        Ok(())
+    }
+}
+
+impl ProcessBackend for SignalBackend {
+    fn suspend(&self, pid: u32) -> Result<()> {
+        Self::signal(pid, libc::SIGSTOP)
+    }
+
+    fn resume(&self, pid: u32) -> Result<()> {
+        Self::signal(pid, libc::SIGCONT)
+    }
+}
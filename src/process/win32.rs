@@ -0,0 +1,74 @@
+use super::ProcessBackend;
+use anyhow::{Context, Result};
+use tracing::warn;
+use windows::Win32::Foundation::{CloseHandle, HANDLE};
+use windows::Win32::System::Diagnostics::ToolHelp::{
+    CreateToolhelp32Snapshot, Thread32First, Thread32Next, TH32CS_SNAPTHREAD, THREADENTRY32,
+};
+use windows::Win32::System::Threading::{
+    OpenThread, ResumeThread, SuspendThread, THREAD_SUSPEND_RESUME,
+};
+
+/// Suspends processes by suspending each of their threads, the way debuggers
+/// and Process Explorer do.
+pub struct ThreadBackend;
+
+impl ProcessBackend for ThreadBackend {
+    fn suspend(&self, pid: u32) -> Result<()> {
+        for_each_thread(pid, |thread| unsafe { SuspendThread(thread) })
+    }
+
+    fn resume(&self, pid: u32) -> Result<()> {
+        for_each_thread(pid, |thread| unsafe { ResumeThread(thread) })
+    }
+}
+
+/// Runs `f` on every thread of `pid`. `f` returns the previous suspend count,
+/// or `u32::MAX` on failure.
+fn for_each_thread(pid: u32, f: impl Fn(HANDLE) -> u32) -> Result<()> {
+    let mut threads = 0;
+    let mut failed = 0;
+
+    unsafe {
+        let snapshot =
+            CreateToolhelp32Snapshot(TH32CS_SNAPTHREAD, 0).context("Failed to list threads")?;
+
+        let mut entry = THREADENTRY32 {
+            dwSize: std::mem::size_of::<THREADENTRY32>() as u32,
+            ..Default::default()
+        };
+        let mut more = Thread32First(snapshot, &mut entry).is_ok();
+        while more {
+            if entry.th32OwnerProcessID == pid {
+                match OpenThread(THREAD_SUSPEND_RESUME, false, entry.th32ThreadID) {
+                    Ok(thread) => {
+                        if f(thread) == u32::MAX {
+                            failed += 1;
+                        }
+                        let _ = CloseHandle(thread);
+                    }
+                    Err(_) => failed += 1,
+                }
+                threads += 1;
+            }
+            more = Thread32Next(snapshot, &mut entry).is_ok();
+        }
+
+        let _ = CloseHandle(snapshot);
+    }
+
+    if threads == 0 {
+        anyhow::bail!("Process {} has no threads", pid);
+    }
+    // Partly done still counts, so a half-suspended process gets resumed later
+    if failed == threads {
+        anyhow::bail!("Failed to change any of the threads of process {}", pid);
+    }
+    if failed > 0 {
+        warn!(
+            "{} of {} threads of process {} failed",
+            failed, threads, pid
+        );
+    }
+    Ok(())
+}
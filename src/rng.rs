@@ -0,0 +1,74 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+/// Largest seed picked when none is configured. Kept within what a
+/// JavaScript number holds exactly, so the dashboard shows the real seed.
+pub const MAX_RANDOM_SEED: u64 = (1 << 53) - 1;
+
+const DELAY_STREAM: u64 = 0;
+const PICK_STREAM: u64 = 1;
+
+/// How far each stream of a `SwapRng` has been drawn, in 32-bit words.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RngPosition {
+    pub delays: u64,
+    pub picks: u64,
+}
+
+/// The swapper's randomness, all derived from one seed.
+///
+/// Stint delays and game picks come from separate ChaCha8 streams, so the
+/// same seed and config give the same delays and the same picks, even when
+/// one of them is drawn more often, e.g. a forced swap rolling an extra delay.
+#[derive(Debug, Clone)]
+pub struct SwapRng {
+    seed: u64,
+    delays: ChaCha8Rng,
+    picks: ChaCha8Rng,
+}
+
+impl SwapRng {
+    pub fn new(seed: u64) -> Self {
+        Self::at(seed, RngPosition::default())
+    }
+
+    /// Seeded at random.
+    pub fn from_entropy() -> Self {
+        Self::new(rand::thread_rng().gen_range(0..=MAX_RANDOM_SEED))
+    }
+
+    /// Picks up `seed`'s streams at `position`, e.g. when a session is resumed.
+    pub fn at(seed: u64, position: RngPosition) -> Self {
+        let stream = |stream, words: u64| {
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            rng.set_stream(stream);
+            rng.set_word_pos(words as u128);
+            rng
+        };
+        Self {
+            seed,
+            delays: stream(DELAY_STREAM, position.delays),
+            picks: stream(PICK_STREAM, position.picks),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn position(&self) -> RngPosition {
+        RngPosition {
+            delays: self.delays.get_word_pos() as u64,
+            picks: self.picks.get_word_pos() as u64,
+        }
+    }
+
+    pub fn delays(&mut self) -> &mut ChaCha8Rng {
+        &mut self.delays
+    }
+
+    pub fn picks(&mut self) -> &mut ChaCha8Rng {
+        &mut self.picks
+    }
+}
@@ -0,0 +1,182 @@
+use crate::config::AppConfig;
+use crate::duration::SwapDuration;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// What happens after the last swap of a script.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScriptEnd {
+    /// The session ends when the last stint is over.
+    #[default]
+    Stop,
+    /// Starts over from the first swap.
+    Loop,
+}
+
+/// One swap of a script: the game to swap in and how long it stays.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptEntry {
+    pub game_name: String,
+    pub exe_name: String,
+    pub duration: SwapDuration,
+}
+
+/// A fixed sequence of swaps, played back as written instead of picking
+/// games and stint lengths at random.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwapScript {
+    pub entries: Vec<ScriptEntry>,
+    pub at_end: ScriptEnd,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ScriptFile {
+    #[serde(default)]
+    at_end: ScriptEnd,
+    #[serde(default)]
+    swaps: Vec<RawEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawEntry {
+    game: String,
+    duration: toml::Value,
+}
+
+impl SwapScript {
+    /// Reads a `.toml` or `.csv` script. Games are matched against the
+    /// configured ones by exe or display name.
+    pub fn load(path: &Path, config: &AppConfig) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read script file: {:?}", path))?;
+        let is_csv = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+
+        let script = if is_csv {
+            Self::parse_csv(&content, config)
+        } else {
+            Self::parse_toml(&content, config)
+        };
+        script.with_context(|| format!("Invalid script file: {:?}", path))
+    }
+
+    /// ```toml
+    /// at_end = "loop"
+    ///
+    /// [[swaps]]
+    /// game = "Celeste.exe"
+    /// duration = "7m"
+    /// ```
+    pub fn parse_toml(content: &str, config: &AppConfig) -> Result<Self> {
+        let file: ScriptFile = toml::from_str(content).context("Failed to parse script")?;
+        let rows = file.swaps.into_iter().enumerate().map(|(i, raw)| {
+            let duration = match raw.duration {
+                toml::Value::String(s) => s,
+                toml::Value::Integer(n) => n.to_string(),
+                other => other.to_string(),
+            };
+            (format!("swap {}", i + 1), raw.game, duration)
+        });
+        Self::build(rows, file.at_end, config)
+    }
+
+    /// One `game,duration` line per swap, with an optional header. Blank
+    /// lines and lines starting with `#` are skipped. CSV scripts stop at the
+    /// end unless told otherwise with `with_end`.
+    pub fn parse_csv(content: &str, config: &AppConfig) -> Result<Self> {
+        let mut rows = Vec::new();
+        for (i, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (game, duration) = line.split_once(',').unwrap_or((line, ""));
+            let (game, duration) = (unquote(game), unquote(duration));
+            if rows.is_empty() && game.eq_ignore_ascii_case("game") {
+                continue;
+            }
+            rows.push((format!("line {}", i + 1), game, duration));
+        }
+        Self::build(rows, ScriptEnd::default(), config)
+    }
+
+    pub fn with_end(mut self, at_end: ScriptEnd) -> Self {
+        self.at_end = at_end;
+        self
+    }
+
+    /// The swap after `played` swaps of the script, or `None` once a
+    /// stopping script has run out.
+    pub fn entry(&self, played: u64) -> Option<&ScriptEntry> {
+        let len = self.entries.len() as u64;
+        match self.at_end {
+            ScriptEnd::Loop if len > 0 => self.entries.get((played % len) as usize),
+            _ => self.entries.get(usize::try_from(played).ok()?),
+        }
+    }
+
+    /// Checks every row and reports all the invalid ones at once.
+    fn build(
+        rows: impl IntoIterator<Item = (String, String, String)>,
+        at_end: ScriptEnd,
+        config: &AppConfig,
+    ) -> Result<Self> {
+        let mut entries = Vec::new();
+        let mut problems = Vec::new();
+
+        for (place, game, duration) in rows {
+            let found = config.games.iter().find(|g| {
+                g.exe_name.eq_ignore_ascii_case(&game) || g.display_name.eq_ignore_ascii_case(&game)
+            });
+            if found.is_none() {
+                problems.push(format!("{}: unknown game {:?}", place, game));
+            }
+            let duration = match duration.parse::<SwapDuration>() {
+                Ok(d) if d.as_secs() == 0 => {
+                    problems.push(format!("{}: duration must be more than zero", place));
+                    None
+                }
+                Ok(d) => Some(d),
+                Err(e) if duration.is_empty() => {
+                    problems.push(format!("{}: missing duration ({})", place, e));
+                    None
+                }
+                Err(e) => {
+                    problems.push(format!("{}: invalid duration {:?}: {}", place, duration, e));
+                    None
+                }
+            };
+
+            if let (Some(game_config), Some(duration)) = (found, duration) {
+                entries.push(ScriptEntry {
+                    game_name: game_config.display_name.clone(),
+                    exe_name: game_config.exe_name.clone(),
+                    duration,
+                });
+            }
+        }
+
+        if !problems.is_empty() {
+            anyhow::bail!("{}", problems.join("\n"));
+        }
+        if entries.is_empty() {
+            anyhow::bail!("The script has no swaps");
+        }
+        Ok(Self { entries, at_end })
+    }
+}
+
+fn unquote(field: &str) -> String {
+    let field = field.trim();
+    field
+        .strip_prefix('"')
+        .and_then(|f| f.strip_suffix('"'))
+        .unwrap_or(field)
+        .to_string()
+}
@@ -0,0 +1,144 @@
+use crate::config::{GameConfig, SelectionStrategy};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::HashMap;
+
+/// What a strategy gets to look at when picking the next game.
+pub struct SelectionContext<'c, 'g> {
+    /// Enabled games, in config order.
+    pub games: &'c [&'g GameConfig],
+    /// Games that can be swapped to right now; a subset of `games`.
+    pub candidates: &'c [&'g GameConfig],
+    pub current_exe: Option<&'c str>,
+    /// Seconds played per game, keyed by display name.
+    pub total_times: &'c HashMap<String, u64>,
+}
+
+/// Picks the next game according to a `SelectionStrategy`, keeping whatever
+/// state a strategy needs between swaps.
+#[derive(Debug, Default)]
+pub struct Selector {
+    bag: Vec<String>,
+}
+
+impl Selector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn pick<'a, R: Rng + ?Sized>(
+        &mut self,
+        strategy: SelectionStrategy,
+        ctx: &SelectionContext<'_, 'a>,
+        rng: &mut R,
+    ) -> Option<&'a GameConfig> {
+        if ctx.candidates.is_empty() {
+            return None;
+        }
+
+        match strategy {
+            SelectionStrategy::Random => ctx.candidates.choose(rng).copied(),
+            SelectionStrategy::Weighted => pick_weighted(ctx.candidates, rng),
+            SelectionStrategy::RoundRobin => pick_round_robin(ctx),
+            SelectionStrategy::ShuffleBag => self.pick_from_bag(ctx, rng),
+            SelectionStrategy::LeastPlayed => pick_least_played(ctx, rng),
+        }
+    }
+
+    /// Forgets per-session state, e.g. when a new session starts.
+    pub fn reset(&mut self) {
+        self.bag.clear();
+    }
+
+    /// Exe names of the games left in the shuffle bag this round. Round
+    /// robin needs nothing saved, it goes by the current game.
+    pub fn bag(&self) -> &[String] {
+        &self.bag
+    }
+
+    /// Carries on with a bag saved from `bag`, e.g. when a session is restored.
+    pub fn restore_bag(&mut self, bag: Vec<String>) {
+        self.bag = bag;
+    }
+
+    /// Records that a game was swapped in, however it was chosen, so the
+    /// shuffle bag doesn't hand it out again this round.
+    pub fn played(&mut self, exe_name: &str) {
+        self.bag.retain(|exe| !exe.eq_ignore_ascii_case(exe_name));
+    }
+
+    fn pick_from_bag<'a, R: Rng + ?Sized>(
+        &mut self,
+        ctx: &SelectionContext<'_, 'a>,
+        rng: &mut R,
+    ) -> Option<&'a GameConfig> {
+        self.bag.retain(|exe| {
+            ctx.games
+                .iter()
+                .any(|g| g.exe_name.eq_ignore_ascii_case(exe))
+        });
+
+        let in_bag = |bag: &[String]| -> Vec<&'a GameConfig> {
+            ctx.candidates
+                .iter()
+                .filter(|g| bag.iter().any(|exe| exe.eq_ignore_ascii_case(&g.exe_name)))
+                .copied()
+                .collect()
+        };
+
+        let mut available = in_bag(&self.bag);
+        if available.is_empty() {
+            self.bag = ctx.games.iter().map(|g| g.exe_name.clone()).collect();
+            available = in_bag(&self.bag);
+        }
+
+        let game = available.choose(rng).copied()?;
+        self.played(&game.exe_name);
+        Some(game)
+    }
+}
+
+fn pick_weighted<'a, R: Rng + ?Sized>(
+    candidates: &[&'a GameConfig],
+    rng: &mut R,
+) -> Option<&'a GameConfig> {
+    let weights = candidates.iter().map(|g| g.weight.max(0.0));
+    match WeightedIndex::new(weights) {
+        Ok(dist) => Some(candidates[dist.sample(rng)]),
+        // Every weight is zero (or invalid), so fall back to a fair pick
+        Err(_) => candidates.choose(rng).copied(),
+    }
+}
+
+fn pick_round_robin<'a>(ctx: &SelectionContext<'_, 'a>) -> Option<&'a GameConfig> {
+    let start = ctx
+        .current_exe
+        .and_then(|exe| {
+            ctx.games
+                .iter()
+                .position(|g| g.exe_name.eq_ignore_ascii_case(exe))
+        })
+        .map(|i| i + 1)
+        .unwrap_or(0);
+
+    (0..ctx.games.len())
+        .map(|offset| ctx.games[(start + offset) % ctx.games.len()])
+        .find(|g| ctx.candidates.iter().any(|c| c.exe_name == g.exe_name))
+}
+
+fn pick_least_played<'a, R: Rng + ?Sized>(
+    ctx: &SelectionContext<'_, 'a>,
+    rng: &mut R,
+) -> Option<&'a GameConfig> {
+    let played = |g: &GameConfig| ctx.total_times.get(&g.display_name).copied().unwrap_or(0);
+    let least = ctx.candidates.iter().map(|g| played(g)).min()?;
+
+    let tied: Vec<&'a GameConfig> = ctx
+        .candidates
+        .iter()
+        .filter(|g| played(g) == least)
+        .copied()
+        .collect();
+    tied.choose(rng).copied()
+}
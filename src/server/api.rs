@@ -1,8 +1,12 @@
+use crate::db::SessionDb;
+use crate::duration::SwapDuration;
+use crate::export::SessionExport;
 use crate::server::ServerState;
-use crate::windows;
+use crate::stats::SessionStats;
+use crate::swapper::InvalidTarget;
 use axum::{
-    extract::State,
-    http::StatusCode,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
     response::IntoResponse,
     routing::{get, post},
     Json, Router,
@@ -21,13 +25,134 @@ pub struct WindowInfoResponse {
 #[derive(Debug, Deserialize)]
 pub struct UpdateConfigRequest {
     pub games: Option<Vec<crate::config::GameConfig>>,
+    pub min_swap: Option<SwapDuration>,
+    pub max_swap: Option<SwapDuration>,
+    /// Whole-minute intervals, accepted for older API clients.
     pub min_swap_minutes: Option<u32>,
     pub max_swap_minutes: Option<u32>,
     pub auto_swap_enabled: Option<bool>,
+    pub selection_strategy: Option<crate::config::SelectionStrategy>,
     pub hide_next_swap: Option<bool>,
     pub obs_ws_host: Option<String>,
     pub obs_ws_port: Option<u16>,
     pub obs_ws_password: Option<Option<String>>,
+    pub summary_template: Option<String>,
+    /// `null` clears the seed so each session picks a random one.
+    #[serde(default, deserialize_with = "explicit_null")]
+    pub seed: Option<Option<u64>>,
+    pub schedule_ahead: Option<u32>,
+    pub focus_settle_ms: Option<u64>,
+    pub focus_timeout_ms: Option<u64>,
+}
+
+/// Tells a field set to `null` apart from one that was left out.
+fn explicit_null<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SwapRequest {
+    pub exe_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QueueRequest {
+    pub exe_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CompleteRequest {
+    pub exe_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HistoryQuery {
+    #[serde(default)]
+    pub offset: usize,
+    #[serde(default = "default_history_limit")]
+    pub limit: usize,
+}
+
+fn default_history_limit() -> usize {
+    50
+}
+
+/// A page of the session history, newest stint first.
+#[derive(Debug, Serialize)]
+pub struct HistoryResponse {
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+    pub items: Vec<crate::state::SwapHistoryItem>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SummaryQuery {
+    /// Overrides the configured `summary_template`.
+    pub template: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewSessionRequest {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CompareQuery {
+    /// Comma-separated session ids; every session when left out.
+    pub ids: Option<String>,
+}
+
+/// A past or running session with its per-game totals.
+#[derive(Debug, Serialize)]
+pub struct SessionDetailResponse {
+    pub session: crate::db::SessionInfo,
+    pub totals: Vec<crate::export::GameTotal>,
+    pub completed: Vec<crate::state::CompletedGame>,
+}
+
+/// Per-game time across sessions; `seconds` lines up with `sessions`.
+#[derive(Debug, Serialize)]
+pub struct CompareResponse {
+    pub sessions: Vec<crate::db::SessionInfo>,
+    pub games: Vec<crate::db::GameComparison>,
+}
+
+/// A planned swap with when it should happen if the timer isn't changed.
+#[derive(Debug, Serialize)]
+pub struct ScheduleEntry {
+    #[serde(flatten)]
+    pub swap: crate::state::ScheduledSwap,
+    pub starts_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScheduleResponse {
+    pub enabled: bool,
+    /// The swaps come from a script file and can't be moved or rerolled.
+    pub scripted: bool,
+    pub entries: Vec<ScheduleEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MoveScheduledRequest {
+    pub id: u64,
+    pub to: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RerollRequest {
+    /// The whole schedule is planned again when left out.
+    pub id: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TimerRequest {
+    pub seconds: i64,
 }
 
 #[derive(Debug, Serialize)]
@@ -38,11 +163,25 @@ pub struct StateResponse {
     pub next_swap_at: Option<chrono::DateTime<chrono::Utc>>,
     pub is_paused: bool,
     pub swap_count: u64,
+    pub queued_game: Option<String>,
+    pub queued_exe: Option<String>,
     pub time_since_swap_seconds: Option<i64>,
     pub time_until_swap_seconds: Option<i64>,
+    /// The latest stints only; see `/api/history` for the rest.
     pub history: Vec<crate::state::SwapHistoryItem>,
+    pub history_len: usize,
     #[serde(default)]
     pub total_times: HashMap<String, u64>,
+    #[serde(default)]
+    pub time_budgets: HashMap<String, u64>,
+    #[serde(default)]
+    pub played_times: HashMap<String, u64>,
+    pub session_ended_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub completed: Vec<crate::state::CompletedGame>,
+    pub session_id: Option<i64>,
+    pub session_name: Option<String>,
+    pub seed: Option<u64>,
+    pub schedule: Vec<crate::state::ScheduledSwap>,
 }
 
 pub fn create_api_router() -> Router<ServerState> {
@@ -50,9 +189,29 @@ pub fn create_api_router() -> Router<ServerState> {
         .route("/api/config", get(get_config).put(update_config))
         .route("/api/windows", get(get_windows))
         .route("/api/state", get(get_state))
+        .route("/api/history", get(get_history))
+        .route("/api/stats", get(get_stats))
+        .route("/api/schedule", get(get_schedule))
+        .route("/api/schedule/move", post(move_scheduled))
+        .route("/api/schedule/reroll", post(reroll_scheduled))
+        .route("/api/export/history.csv", get(export_history_csv))
+        .route("/api/export/totals.csv", get(export_totals_csv))
+        .route("/api/export/session.json", get(export_json))
+        .route("/api/export/summary.txt", get(export_summary))
         .route("/api/swap", post(force_swap))
+        .route("/api/queue", post(queue_next))
+        .route("/api/complete", post(complete_game))
+        .route("/api/timer/adjust", post(adjust_timer))
+        .route("/api/timer/set", post(set_timer))
         .route("/api/pause", post(pause))
         .route("/api/resume", post(resume))
+        .route("/api/session", get(get_session))
+        .route("/api/session/resume", post(resume_session))
+        .route("/api/session/discard", post(discard_session))
+        .route("/api/sessions", get(list_sessions).post(new_session))
+        .route("/api/sessions/compare", get(compare_sessions))
+        .route("/api/sessions/:id", get(get_session_detail))
+        .route("/api/sessions/:id/history", get(get_session_history))
 }
 
 async fn get_config(State(state): State<ServerState>) -> impl IntoResponse {
@@ -71,14 +230,23 @@ async fn update_config(
                 c.games = games;
             }
             if let Some(min) = req.min_swap_minutes {
-                c.min_swap_minutes = min;
+                c.min_swap = SwapDuration::from_minutes(min as u64);
             }
             if let Some(max) = req.max_swap_minutes {
-                c.max_swap_minutes = max;
+                c.max_swap = SwapDuration::from_minutes(max as u64);
+            }
+            if let Some(min) = req.min_swap {
+                c.min_swap = min;
+            }
+            if let Some(max) = req.max_swap {
+                c.max_swap = max;
             }
             if let Some(enabled) = req.auto_swap_enabled {
                 c.auto_swap_enabled = enabled;
             }
+            if let Some(strategy) = req.selection_strategy {
+                c.selection_strategy = strategy;
+            }
             if let Some(hide) = req.hide_next_swap {
                 c.hide_next_swap = hide;
             }
@@ -91,11 +259,31 @@ async fn update_config(
             if let Some(pass) = req.obs_ws_password {
                 c.obs_ws_password = pass;
             }
+            if let Some(template) = req.summary_template {
+                c.summary_template = template;
+            }
+            if let Some(seed) = req.seed {
+                c.seed = seed;
+            }
+            if let Some(ahead) = req.schedule_ahead {
+                c.schedule_ahead = ahead;
+            }
+            if let Some(settle) = req.focus_settle_ms {
+                c.focus_settle_ms = settle;
+            }
+            if let Some(timeout) = req.focus_timeout_ms {
+                c.focus_timeout_ms = timeout;
+            }
         })
         .await;
 
     match config {
-        Ok(c) => (StatusCode::OK, Json(c)).into_response(),
+        Ok(c) => {
+            if let Err(e) = state.swapper.config_changed().await {
+                warn!("Failed to notify swapper of config change: {:?}", e);
+            }
+            (StatusCode::OK, Json(c)).into_response()
+        }
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(serde_json::json!({ "error": e.to_string() })),
@@ -104,8 +292,8 @@ async fn update_config(
     }
 }
 
-async fn get_windows() -> impl IntoResponse {
-    match windows::enumerate_windows() {
+async fn get_windows(State(state): State<ServerState>) -> impl IntoResponse {
+    match state.windows.enumerate_windows() {
         Ok(windows) => {
             let response: Vec<WindowInfoResponse> = windows
                 .into_iter()
@@ -126,11 +314,7 @@ async fn get_windows() -> impl IntoResponse {
 }
 
 async fn get_state(State(state): State<ServerState>) -> impl IntoResponse {
-    let swap_state = state.app_state.get_state().await;
-    let now = chrono::Utc::now();
-
-    let time_since_swap_seconds = swap_state.last_swap_at.map(|t| (now - t).num_seconds());
-    let time_until_swap_seconds = swap_state.next_swap_at.map(|t| (t - now).num_seconds());
+    let swap_state = state.app_state.get_state().await.for_clients();
 
     Json(StateResponse {
         current_game: swap_state.current_game,
@@ -139,25 +323,284 @@ async fn get_state(State(state): State<ServerState>) -> impl IntoResponse {
         next_swap_at: swap_state.next_swap_at,
         is_paused: swap_state.is_paused,
         swap_count: swap_state.swap_count,
-        time_since_swap_seconds,
-        time_until_swap_seconds,
+        queued_game: swap_state.queued_game,
+        queued_exe: swap_state.queued_exe,
+        time_since_swap_seconds: swap_state.time_since_swap_seconds,
+        time_until_swap_seconds: swap_state.time_until_swap_seconds,
         history: swap_state.history,
+        history_len: swap_state.history_len,
         total_times: swap_state.total_times,
+        time_budgets: swap_state.time_budgets,
+        played_times: swap_state.played_times,
+        session_ended_at: swap_state.session_ended_at,
+        completed: swap_state.completed,
+        session_id: swap_state.session_id,
+        session_name: swap_state.session_name,
+        seed: swap_state.seed,
+        schedule: swap_state.schedule,
+    })
+}
+
+async fn get_history(
+    State(state): State<ServerState>,
+    Query(query): Query<HistoryQuery>,
+) -> impl IntoResponse {
+    let limit = query.limit.min(500);
+    let history = state.app_state.get_state().await.history;
+    let items = history
+        .iter()
+        .rev()
+        .skip(query.offset)
+        .take(limit)
+        .cloned()
+        .collect();
+
+    Json(HistoryResponse {
+        total: history.len(),
+        offset: query.offset,
+        limit,
+        items,
     })
 }
 
-async fn force_swap(State(state): State<ServerState>) -> impl IntoResponse {
-    info!("Force swap requested");
-    match state.swapper.force_swap().await {
+async fn get_stats(State(state): State<ServerState>) -> impl IntoResponse {
+    let swap_state = state.app_state.get_state().await;
+    let config = state.config_manager.get().await;
+    Json(SessionStats::new(
+        &swap_state,
+        &config,
+        state.app_state.clock.now(),
+    ))
+}
+
+async fn schedule_response(state: &ServerState) -> ScheduleResponse {
+    let config = state.config_manager.get().await;
+    let swap_state = state.app_state.get_state().await;
+    let now = state.app_state.clock.now();
+
+    let mut starts_at = swap_state.time_until_swap(now).map(|until| now + until);
+    let entries = swap_state
+        .schedule
+        .into_iter()
+        .map(|swap| {
+            let entry_starts_at = starts_at;
+            starts_at =
+                starts_at.map(|at| at + chrono::Duration::seconds(swap.duration_seconds as i64));
+            ScheduleEntry {
+                swap,
+                starts_at: entry_starts_at,
+            }
+        })
+        .collect();
+
+    ScheduleResponse {
+        enabled: config.schedule_ahead > 0,
+        scripted: state.swapper.is_scripted(),
+        entries,
+    }
+}
+
+async fn get_schedule(State(state): State<ServerState>) -> impl IntoResponse {
+    Json(schedule_response(&state).await)
+}
+
+async fn move_scheduled(
+    State(state): State<ServerState>,
+    Json(req): Json<MoveScheduledRequest>,
+) -> impl IntoResponse {
+    info!("Move of scheduled swap {} to {} requested", req.id, req.to);
+    let result = state.swapper.move_scheduled(req.id, req.to).await;
+    schedule_change_response(&state, result).await
+}
+
+async fn reroll_scheduled(
+    State(state): State<ServerState>,
+    Json(req): Json<RerollRequest>,
+) -> impl IntoResponse {
+    info!("Reroll of scheduled swap {:?} requested", req.id);
+    let result = state.swapper.reroll_scheduled(req.id).await;
+    schedule_change_response(&state, result).await
+}
+
+async fn schedule_change_response(
+    state: &ServerState,
+    result: anyhow::Result<()>,
+) -> axum::response::Response {
+    match result {
+        Ok(()) => (StatusCode::OK, Json(schedule_response(state).await)).into_response(),
+        Err(e) => {
+            warn!("Schedule change failed: {:?}", e);
+            (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": e.to_string() })),
+            )
+                .into_response()
+        }
+    }
+}
+
+async fn session_export(state: &ServerState) -> SessionExport {
+    let swap_state = state.app_state.get_state().await;
+    SessionExport::new(&swap_state, state.app_state.clock.now())
+}
+
+/// A download response, so browsers save the export instead of showing it.
+fn download(content_type: &'static str, filename: &str, body: String) -> axum::response::Response {
+    (
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+async fn export_history_csv(State(state): State<ServerState>) -> impl IntoResponse {
+    let export = session_export(&state).await;
+    download("text/csv", "history.csv", export.history_csv())
+}
+
+async fn export_totals_csv(State(state): State<ServerState>) -> impl IntoResponse {
+    let export = session_export(&state).await;
+    download("text/csv", "totals.csv", export.totals_csv())
+}
+
+async fn export_json(State(state): State<ServerState>) -> impl IntoResponse {
+    let export = session_export(&state).await;
+    let body = serde_json::to_string_pretty(&export).unwrap_or_default();
+    download("application/json", "session.json", body)
+}
+
+async fn export_summary(
+    State(state): State<ServerState>,
+    Query(query): Query<SummaryQuery>,
+) -> impl IntoResponse {
+    let template = match query.template {
+        Some(template) => template,
+        None => state.config_manager.get().await.summary_template,
+    };
+    let export = session_export(&state).await;
+    (
+        [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+        export.summary(&template),
+    )
+}
+
+async fn force_swap(
+    State(state): State<ServerState>,
+    req: Option<Json<SwapRequest>>,
+) -> impl IntoResponse {
+    let target = req.and_then(|Json(r)| r.exe_name);
+    let result = match &target {
+        Some(exe_name) => {
+            info!("Force swap to {} requested", exe_name);
+            state.swapper.swap_to(exe_name).await
+        }
+        None => {
+            info!("Force swap requested");
+            state.swapper.force_swap().await
+        }
+    };
+
+    match result {
         Ok(()) => {
             info!("Force swap completed successfully");
-            let swap_state = state.app_state.get_state().await;
+            let swap_state = state.app_state.get_state().await.for_clients();
             (StatusCode::OK, Json(swap_state)).into_response()
         }
         Err(e) => {
             warn!("Force swap failed: {:?}", e);
+            let status = if e.is::<InvalidTarget>() {
+                StatusCode::BAD_REQUEST
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+            (status, Json(serde_json::json!({ "error": e.to_string() }))).into_response()
+        }
+    }
+}
+
+async fn queue_next(
+    State(state): State<ServerState>,
+    Json(req): Json<QueueRequest>,
+) -> impl IntoResponse {
+    match state.swapper.queue_next(req.exe_name.as_deref()).await {
+        Ok(()) => {
+            let swap_state = state.app_state.get_state().await.for_clients();
+            (StatusCode::OK, Json(swap_state)).into_response()
+        }
+        Err(e) => {
+            warn!("Queue next game failed: {:?}", e);
             (
-                StatusCode::INTERNAL_SERVER_ERROR,
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": e.to_string() })),
+            )
+                .into_response()
+        }
+    }
+}
+
+async fn complete_game(
+    State(state): State<ServerState>,
+    Json(req): Json<CompleteRequest>,
+) -> impl IntoResponse {
+    info!("Completion of {} requested", req.exe_name);
+    match state.swapper.complete(&req.exe_name).await {
+        Ok(()) => {
+            let swap_state = state.app_state.get_state().await.for_clients();
+            (StatusCode::OK, Json(swap_state)).into_response()
+        }
+        Err(e) => {
+            warn!("Complete game failed: {:?}", e);
+            (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": e.to_string() })),
+            )
+                .into_response()
+        }
+    }
+}
+
+async fn adjust_timer(
+    State(state): State<ServerState>,
+    Json(req): Json<TimerRequest>,
+) -> impl IntoResponse {
+    info!("Timer adjust requested: {:+}s", req.seconds);
+    timer_response(&state, state.swapper.adjust_timer(req.seconds).await).await
+}
+
+async fn set_timer(
+    State(state): State<ServerState>,
+    Json(req): Json<TimerRequest>,
+) -> impl IntoResponse {
+    info!("Timer set requested: {}s", req.seconds);
+    if req.seconds < 0 {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "seconds must not be negative" })),
+        )
+            .into_response();
+    }
+    timer_response(&state, state.swapper.set_timer(req.seconds as u64).await).await
+}
+
+async fn timer_response(
+    state: &ServerState,
+    result: anyhow::Result<()>,
+) -> axum::response::Response {
+    match result {
+        Ok(()) => {
+            let swap_state = state.app_state.get_state().await.for_clients();
+            (StatusCode::OK, Json(swap_state)).into_response()
+        }
+        Err(e) => {
+            warn!("Timer change failed: {:?}", e);
+            (
+                StatusCode::CONFLICT,
                 Json(serde_json::json!({ "error": e.to_string() })),
             )
                 .into_response()
@@ -166,13 +609,233 @@ async fn force_swap(State(state): State<ServerState>) -> impl IntoResponse {
 }
 
 async fn pause(State(state): State<ServerState>) -> impl IntoResponse {
-    state.app_state.update_state(|s| s.is_paused = true).await;
-    let swap_state = state.app_state.get_state().await;
+    if let Err(e) = state.swapper.pause().await {
+        warn!("Pause failed: {:?}", e);
+    }
+    let swap_state = state.app_state.get_state().await.for_clients();
     Json(swap_state)
 }
 
 async fn resume(State(state): State<ServerState>) -> impl IntoResponse {
-    state.app_state.update_state(|s| s.is_paused = false).await;
-    let swap_state = state.app_state.get_state().await;
+    if let Err(e) = state.swapper.resume().await {
+        warn!("Resume failed: {:?}", e);
+    }
+    let swap_state = state.app_state.get_state().await.for_clients();
     Json(swap_state)
 }
+
+async fn get_session(State(state): State<ServerState>) -> impl IntoResponse {
+    Json(serde_json::json!({ "previous": state.session.previous() }))
+}
+
+async fn resume_session(State(state): State<ServerState>) -> impl IntoResponse {
+    let Some(snapshot) = state.session.take_previous() else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "No previous session to resume" })),
+        )
+            .into_response();
+    };
+
+    info!("Resuming session saved at {}", snapshot.saved_at);
+    match state.swapper.restore(snapshot.clone()).await {
+        Ok(()) => {
+            let swap_state = state.app_state.get_state().await.for_clients();
+            (StatusCode::OK, Json(swap_state)).into_response()
+        }
+        Err(e) => {
+            warn!("Resume session failed: {:?}", e);
+            // Keep offering it, it's the only copy
+            state.session.set_previous(Some(snapshot));
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": e.to_string() })),
+            )
+                .into_response()
+        }
+    }
+}
+
+async fn discard_session(State(state): State<ServerState>) -> impl IntoResponse {
+    if let Some(snapshot) = state.session.take_previous() {
+        info!("Discarded previous session");
+        // It won't be resumed, so it ended when it was last saved
+        let ended_at = snapshot.saved_at;
+        if let Err(e) = with_db(&state, move |db| db.finish(&snapshot.state, ended_at)).await {
+            warn!("Failed to close the discarded session: {:?}", e);
+        }
+    }
+    Json(serde_json::json!({ "previous": null }))
+}
+
+fn db_error(e: anyhow::Error) -> axum::response::Response {
+    warn!("Session database error: {:?}", e);
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(serde_json::json!({ "error": e.to_string() })),
+    )
+        .into_response()
+}
+
+fn session_not_found(id: i64) -> axum::response::Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(serde_json::json!({ "error": format!("No session with id {}", id) })),
+    )
+        .into_response()
+}
+
+/// Runs `f` against the session database on the blocking pool, since
+/// rusqlite calls would hold up the runtime.
+async fn with_db<T: Send + 'static>(
+    state: &ServerState,
+    f: impl FnOnce(&SessionDb) -> anyhow::Result<T> + Send + 'static,
+) -> anyhow::Result<T> {
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || f(&db)).await?
+}
+
+async fn list_sessions(State(state): State<ServerState>) -> impl IntoResponse {
+    match with_db(&state, |db| db.sessions()).await {
+        Ok(sessions) => Json(sessions).into_response(),
+        Err(e) => db_error(e),
+    }
+}
+
+async fn new_session(
+    State(state): State<ServerState>,
+    Json(req): Json<NewSessionRequest>,
+) -> impl IntoResponse {
+    let name = req.name.trim();
+    if name.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "Session name must not be empty" })),
+        )
+            .into_response();
+    }
+
+    let config = state.config_manager.get().await;
+    let now = state.app_state.clock.now();
+    let session_name = name.to_string();
+    let created = with_db(&state, move |db| {
+        db.create_session(&session_name, now, &config)
+    })
+    .await;
+    let id = match created {
+        Ok(id) => id,
+        Err(e) => return db_error(e),
+    };
+
+    info!("New session {:?} requested", name);
+    match state.swapper.new_session(id, name).await {
+        Ok(()) => {
+            let swap_state = state.app_state.get_state().await.for_clients();
+            (StatusCode::OK, Json(swap_state)).into_response()
+        }
+        Err(e) => {
+            warn!("New session failed: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": e.to_string() })),
+            )
+                .into_response()
+        }
+    }
+}
+
+async fn get_session_detail(
+    State(state): State<ServerState>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    let detail = with_db(&state, move |db| {
+        let Some(session) = db.session(id)? else {
+            return Ok(None);
+        };
+        Ok(Some(SessionDetailResponse {
+            session,
+            totals: db.totals(id)?,
+            completed: db.completions(id)?,
+        }))
+    })
+    .await;
+    match detail {
+        Ok(Some(detail)) => Json(detail).into_response(),
+        Ok(None) => session_not_found(id),
+        Err(e) => db_error(e),
+    }
+}
+
+async fn get_session_history(
+    State(state): State<ServerState>,
+    Path(id): Path<i64>,
+    Query(query): Query<HistoryQuery>,
+) -> impl IntoResponse {
+    let limit = query.limit.min(500);
+    let offset = query.offset;
+    let history = with_db(&state, move |db| match db.session(id)? {
+        Some(_) => db.history(id, offset, limit).map(Some),
+        None => Ok(None),
+    })
+    .await;
+    match history {
+        Ok(None) => session_not_found(id),
+        Ok(Some((total, items))) => Json(HistoryResponse {
+            total,
+            offset: query.offset,
+            limit,
+            items,
+        })
+        .into_response(),
+        Err(e) => db_error(e),
+    }
+}
+
+async fn compare_sessions(
+    State(state): State<ServerState>,
+    Query(query): Query<CompareQuery>,
+) -> impl IntoResponse {
+    let ids = match &query.ids {
+        Some(ids) => {
+            let mut parsed = Vec::new();
+            for id in ids.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+                let Ok(id) = id.parse::<i64>() else {
+                    return (
+                        StatusCode::BAD_REQUEST,
+                        Json(serde_json::json!({ "error": format!("Invalid session id: {}", id) })),
+                    )
+                        .into_response();
+                };
+                parsed.push(id);
+            }
+            Some(parsed)
+        }
+        None => None,
+    };
+
+    // Err(id) names a session that doesn't exist
+    let compared = with_db(&state, move |db| {
+        let sessions = match ids {
+            Some(ids) => {
+                let mut sessions = Vec::new();
+                for id in ids {
+                    match db.session(id)? {
+                        Some(session) => sessions.push(session),
+                        None => return Ok(Err(id)),
+                    }
+                }
+                sessions
+            }
+            None => db.sessions()?,
+        };
+        let ids: Vec<i64> = sessions.iter().map(|s| s.id).collect();
+        let games = db.compare(&ids)?;
+        Ok(Ok(CompareResponse { sessions, games }))
+    })
+    .await;
+    match compared {
+        Ok(Ok(response)) => Json(response).into_response(),
+        Ok(Err(id)) => session_not_found(id),
+        Err(e) => db_error(e),
+    }
+}
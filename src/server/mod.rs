@@ -1,9 +1,13 @@
 pub mod api;
+pub mod simulate;
 pub mod ws;
 
 use crate::config::ConfigManager;
+use crate::db::SessionDb;
+use crate::session::SessionStore;
 use crate::state::AppState;
 use crate::swapper::Swapper;
+use crate::windows::{MockWindowBackend, WindowBackend};
 use axum::{Router, routing::get};
 use std::sync::Arc;
 use tower_http::cors::CorsLayer;
@@ -14,22 +18,35 @@ pub struct ServerState {
     pub config_manager: Arc<ConfigManager>,
     pub app_state: Arc<AppState>,
     pub swapper: Arc<Swapper>,
+    pub session: Arc<SessionStore>,
+    pub db: Arc<SessionDb>,
+    pub windows: Arc<dyn WindowBackend>,
+    pub simulation: Option<Arc<MockWindowBackend>>,
 }
 
 pub fn create_app(
     config_manager: Arc<ConfigManager>,
     app_state: Arc<AppState>,
     swapper: Arc<Swapper>,
+    session: Arc<SessionStore>,
+    db: Arc<SessionDb>,
+    windows: Arc<dyn WindowBackend>,
+    simulation: Option<Arc<MockWindowBackend>>,
 ) -> Router {
     let state = ServerState {
         config_manager,
         app_state,
         swapper,
+        session,
+        db,
+        windows,
+        simulation,
     };
 
     Router::new()
         .route("/ws", get(ws::ws_handler))
         .merge(api::create_api_router())
+        .merge(simulate::create_simulate_router())
         .nest_service("/", ServeDir::new("static"))
         .layer(CorsLayer::permissive())
         .with_state(state)
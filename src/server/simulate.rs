@@ -0,0 +1,105 @@
+use crate::server::ServerState;
+use crate::windows::{MockWindowBackend, MockWindowConfig};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{get, put},
+    Json, Router,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateWindowRequest {
+    pub focus_fails: Option<bool>,
+    pub focus_ignored: Option<bool>,
+}
+
+/// Routes for scripting fake windows while running in `--simulate` mode.
+pub fn create_simulate_router() -> Router<ServerState> {
+    Router::new()
+        .route("/api/simulate/windows", get(list_windows).post(add_window))
+        .route(
+            "/api/simulate/windows/:exe_name",
+            put(update_window).delete(remove_window),
+        )
+}
+
+fn backend(state: &ServerState) -> Option<&Arc<MockWindowBackend>> {
+    state.simulation.as_ref()
+}
+
+fn not_simulating() -> axum::response::Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(serde_json::json!({ "error": "Not running in simulation mode" })),
+    )
+        .into_response()
+}
+
+fn window_not_found(exe_name: &str) -> axum::response::Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(serde_json::json!({ "error": format!("No simulated window for {}", exe_name) })),
+    )
+        .into_response()
+}
+
+async fn list_windows(State(state): State<ServerState>) -> impl IntoResponse {
+    match backend(&state) {
+        Some(mock) => Json(mock.snapshot()).into_response(),
+        None => not_simulating(),
+    }
+}
+
+async fn add_window(
+    State(state): State<ServerState>,
+    Json(req): Json<MockWindowConfig>,
+) -> impl IntoResponse {
+    let Some(mock) = backend(&state) else {
+        return not_simulating();
+    };
+
+    mock.add(req);
+    (StatusCode::CREATED, Json(mock.snapshot())).into_response()
+}
+
+async fn update_window(
+    State(state): State<ServerState>,
+    Path(exe_name): Path<String>,
+    Json(req): Json<UpdateWindowRequest>,
+) -> impl IntoResponse {
+    let Some(mock) = backend(&state) else {
+        return not_simulating();
+    };
+
+    let exists = mock
+        .snapshot()
+        .iter()
+        .any(|w| w.exe_name.eq_ignore_ascii_case(&exe_name));
+    if !exists {
+        return window_not_found(&exe_name);
+    }
+    if let Some(fails) = req.focus_fails {
+        mock.set_focus_fails(&exe_name, fails);
+    }
+    if let Some(ignored) = req.focus_ignored {
+        mock.set_focus_ignored(&exe_name, ignored);
+    }
+    Json(mock.snapshot()).into_response()
+}
+
+async fn remove_window(
+    State(state): State<ServerState>,
+    Path(exe_name): Path<String>,
+) -> impl IntoResponse {
+    let Some(mock) = backend(&state) else {
+        return not_simulating();
+    };
+
+    if !mock.remove(&exe_name) {
+        return window_not_found(&exe_name);
+    }
+    Json(mock.snapshot()).into_response()
+}
@@ -17,17 +17,32 @@ pub async fn ws_handler(
 async fn handle_socket(socket: WebSocket, app_state: std::sync::Arc<crate::state::AppState>) {
     let (mut tx, mut rx) = socket.split();
     let mut receiver = app_state.broadcaster.subscribe();
+    let mut events = app_state.broadcaster.subscribe_events();
 
     let initial_state = app_state.get_state().await;
-    let msg = serde_json::to_string(&initial_state).unwrap_or_default();
+    let msg = serde_json::to_string(&initial_state.for_clients()).unwrap_or_default();
     if tx.send(Message::Text(msg)).await.is_err() {
         return;
     }
 
+    // State updates go out as the bare state; events are tagged with an "event" field
     let send_task = async move {
-        while let Ok(state) = receiver.recv().await {
-            let msg = serde_json::to_string(&state).unwrap_or_default();
-            if tx.send(Message::Text(msg)).await.is_err() {
+        loop {
+            let msg = tokio::select! {
+                state = receiver.recv() => match state {
+                    Ok(state) => serde_json::to_string(&state.for_clients()),
+                    Err(_) => break,
+                },
+                event = events.recv() => match event {
+                    Ok(event) => serde_json::to_string(&event),
+                    Err(_) => break,
+                },
+            };
+            if tx
+                .send(Message::Text(msg.unwrap_or_default()))
+                .await
+                .is_err()
+            {
                 break;
             }
         }
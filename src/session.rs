@@ -0,0 +1,160 @@
+use crate::state::{AppState, SwapState};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{error, info};
+
+/// `SwapState` as written to disk, with the moment it was taken.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionSnapshot {
+    pub saved_at: DateTime<Utc>,
+    pub state: SwapState,
+}
+
+impl SessionSnapshot {
+    /// The state to carry on with at `now`. The time the app was down is
+    /// treated like a pause, so neither the stint nor the countdown moves.
+    pub fn into_state(self, now: DateTime<Utc>) -> SwapState {
+        let mut state = self.state;
+        if !state.is_paused {
+            state.pause(self.saved_at);
+            state.resume(now);
+        }
+        state
+    }
+}
+
+/// What the dashboard shows about a previous session it can resume.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionSummary {
+    pub saved_at: DateTime<Utc>,
+    pub current_game: Option<String>,
+    pub swap_count: u64,
+}
+
+impl From<&SessionSnapshot> for SessionSummary {
+    fn from(snapshot: &SessionSnapshot) -> Self {
+        Self {
+            saved_at: snapshot.saved_at,
+            current_game: snapshot.state.current_game.clone(),
+            swap_count: snapshot.state.swap_count,
+        }
+    }
+}
+
+/// Keeps the running session on disk, and the previous one around until the
+/// operator resumes or discards it.
+pub struct SessionStore {
+    path: PathBuf,
+    previous: Mutex<Option<SessionSnapshot>>,
+}
+
+impl SessionStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            previous: Mutex::new(None),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reads the last saved session, if there is one.
+    pub fn load(&self) -> Result<Option<SessionSnapshot>> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+
+        let content = std::fs::read_to_string(&self.path)
+            .with_context(|| format!("Failed to read session file: {:?}", self.path))?;
+        let snapshot = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse session file: {:?}", self.path))?;
+        Ok(Some(snapshot))
+    }
+
+    pub async fn save(&self, snapshot: &SessionSnapshot) -> Result<()> {
+        let content = serde_json::to_string_pretty(snapshot)
+            .with_context(|| "Failed to serialize session")?;
+
+        // Write next to the real file and swap it in, so a crash mid-write
+        // can't leave a truncated session behind
+        let tmp = self.path.with_extension("json.tmp");
+        tokio::fs::write(&tmp, content)
+            .await
+            .with_context(|| format!("Failed to write session file: {:?}", tmp))?;
+        tokio::fs::rename(&tmp, &self.path)
+            .await
+            .with_context(|| format!("Failed to write session file: {:?}", self.path))
+    }
+
+    /// Keeps `snapshot` as the session the dashboard offers to resume.
+    pub fn set_previous(&self, snapshot: Option<SessionSnapshot>) {
+        *self.previous.lock().unwrap() = snapshot;
+    }
+
+    pub fn previous(&self) -> Option<SessionSummary> {
+        self.previous
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(SessionSummary::from)
+    }
+
+    pub fn take_previous(&self) -> Option<SessionSnapshot> {
+        self.previous.lock().unwrap().take()
+    }
+}
+
+/// Whether there's anything in `state` worth writing over the previous session.
+fn has_progress(state: &SwapState) -> bool {
+    state.swap_count > 0 || state.current_game.is_some() || !state.completed.is_empty()
+}
+
+/// Saves the session every `interval`, and right away whenever a swap or
+/// completion happens.
+pub async fn autosave(store: Arc<SessionStore>, app_state: Arc<AppState>, interval: Duration) {
+    let mut updates = app_state.broadcaster.subscribe();
+    let mut ticker = tokio::time::interval(interval);
+    let mut last_saved: Option<(u64, usize)> = None;
+
+    loop {
+        let state = tokio::select! {
+            _ = ticker.tick() => app_state.get_state().await,
+            update = updates.recv() => match update {
+                Ok(state) => {
+                    let milestone = (state.swap_count, state.completed.len());
+                    if last_saved == Some(milestone) {
+                        continue;
+                    }
+                    state
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(_) => return,
+            },
+        };
+
+        if !has_progress(&state) {
+            continue;
+        }
+
+        let milestone = (state.swap_count, state.completed.len());
+        let snapshot = SessionSnapshot {
+            saved_at: app_state.clock.now(),
+            state,
+        };
+        match store.save(&snapshot).await {
+            Ok(()) => {
+                if last_saved != Some(milestone) {
+                    info!("Session saved to {:?}", store.path());
+                }
+                last_saved = Some(milestone);
+            }
+            Err(e) => error!("Failed to save session: {:?}", e),
+        }
+    }
+}
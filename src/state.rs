@@ -1,16 +1,95 @@
+use crate::clock::{Clock, SystemClock};
+use crate::rng::RngPosition;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::{broadcast, RwLock};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// How a stint came about.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SwapTrigger {
+    /// The timer ran out.
+    #[default]
+    Automatic,
+    /// The operator swapped by hand, e.g. "Swap Now" or completing the active game.
+    Forced,
+    /// The timer ran out and the game the operator queued came up.
+    Queued,
+    /// The timer ran out and the script's next game came up.
+    Scripted,
+}
+
+impl SwapTrigger {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SwapTrigger::Automatic => "automatic",
+            SwapTrigger::Forced => "forced",
+            SwapTrigger::Queued => "queued",
+            SwapTrigger::Scripted => "scripted",
+        }
+    }
+}
+
+/// A swap planned ahead of time: the game to swap in and how long it stays.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduledSwap {
+    /// Stable while the entry is waiting, so it can be moved or rerolled.
+    pub id: u64,
+    pub game_name: String,
+    pub exe_name: String,
+    pub duration_seconds: u64,
+}
+
+/// One finished stint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SwapHistoryItem {
     pub game_name: String,
+    #[serde(default)]
+    pub exe_name: String,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    /// Time played, excluding `paused_seconds`.
     pub duration_seconds: u64,
+    #[serde(default)]
+    pub paused_seconds: u64,
+    #[serde(default)]
+    pub trigger: SwapTrigger,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// How many of the latest stints are pushed to clients with every state
+/// update. The rest is available from `/api/history`.
+pub const RECENT_HISTORY: usize = 10;
+
+/// A game the runner finished during the session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompletedGame {
+    pub game_name: String,
+    pub exe_name: String,
+    pub completed_at: DateTime<Utc>,
+    /// Total time played in the game when it was completed.
+    pub total_seconds: u64,
+}
+
+/// A swap that was given up, e.g. because the game never came to the front.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SwapFailure {
+    pub game_name: String,
+    pub exe_name: String,
+    pub reason: String,
+    pub failed_at: DateTime<Utc>,
+}
+
+/// One-off happenings pushed to websocket clients alongside state updates.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum SessionEvent {
+    GameCompleted(CompletedGame),
+    SwapFailed(SwapFailure),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[derive(Default)]
 pub struct SwapState {
     pub current_game: Option<String>,
@@ -23,22 +102,241 @@ pub struct SwapState {
     pub time_since_swap_seconds: Option<i64>,
     #[serde(default)]
     pub time_until_swap_seconds: Option<i64>,
+    /// Every finished stint of the session, oldest first. Clients only get
+    /// the latest few, see `for_clients`.
     #[serde(default)]
     pub history: Vec<SwapHistoryItem>,
+    /// Length of the full history, even when `history` has been trimmed.
+    #[serde(default)]
+    pub history_len: usize,
     #[serde(default)]
     pub total_times: HashMap<String, u64>,
+    #[serde(default)]
+    pub queued_game: Option<String>,
+    #[serde(default)]
+    pub queued_exe: Option<String>,
+    #[serde(default)]
+    pub paused_at: Option<DateTime<Utc>>,
+    /// Paused time inside the current stint, not counting an ongoing pause.
+    #[serde(default)]
+    pub stint_paused_ms: i64,
+    /// How the current stint started.
+    #[serde(default)]
+    pub stint_trigger: SwapTrigger,
+    /// Time budget in seconds for each game that has one, keyed by exe name.
+    #[serde(default)]
+    pub time_budgets: HashMap<String, u64>,
+    /// Seconds played in each game's finished stints, keyed by exe name.
+    /// Worked out from the full history, which clients only get part of.
+    #[serde(default)]
+    pub played_times: HashMap<String, u64>,
+    /// Set once the session is over, e.g. every game used up its time budget.
+    #[serde(default)]
+    pub session_ended_at: Option<DateTime<Utc>>,
+    /// Games completed this session, oldest first.
+    #[serde(default)]
+    pub completed: Vec<CompletedGame>,
+    /// Id of the session in the session database, if it's being recorded.
+    #[serde(default)]
+    pub session_id: Option<i64>,
+    #[serde(default)]
+    pub session_name: Option<String>,
+    /// Seed of the delays and picks this session, see `SwapRng`.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub rng_position: RngPosition,
+    /// Exe names of the games left in the shuffle bag this round, so a
+    /// resumed session keeps drawing the same way.
+    #[serde(default)]
+    pub shuffle_bag: Vec<String>,
+    /// Upcoming swaps, next first, when `schedule_ahead` is set.
+    #[serde(default)]
+    pub schedule: Vec<ScheduledSwap>,
+    /// Id for the next scheduled swap.
+    #[serde(default)]
+    pub schedule_seq: u64,
+    /// Swaps of the script played this session, loops included.
+    #[serde(default)]
+    pub script_position: u64,
+}
+
+impl SwapState {
+    /// Freezes the countdown; the remaining time is kept in `next_swap_at`.
+    pub fn pause(&mut self, now: DateTime<Utc>) {
+        if self.is_paused {
+            return;
+        }
+        self.is_paused = true;
+        self.paused_at = Some(now);
+    }
+
+    /// Resumes the countdown from where it stopped.
+    pub fn resume(&mut self, now: DateTime<Utc>) {
+        if !self.is_paused {
+            return;
+        }
+        self.is_paused = false;
+
+        if let Some(paused_at) = self.paused_at.take() {
+            let paused_for = (now - paused_at).max(chrono::Duration::zero());
+            self.next_swap_at = self.next_swap_at.map(|t| t + paused_for);
+            if self.last_swap_at.is_some() {
+                self.stint_paused_ms += paused_for.num_milliseconds();
+            }
+        }
+    }
+
+    /// Starts a new stint at `now`, carrying an ongoing pause over into it.
+    pub fn start_stint(&mut self, now: DateTime<Utc>, trigger: SwapTrigger) {
+        self.last_swap_at = Some(now);
+        self.stint_paused_ms = 0;
+        self.stint_trigger = trigger;
+        if self.is_paused {
+            self.paused_at = Some(now);
+        }
+    }
+
+    /// Time spent in the current game so far, excluding paused spans.
+    pub fn stint_duration(&self, now: DateTime<Utc>) -> Option<chrono::Duration> {
+        let started = self.last_swap_at?;
+        let mut paused = chrono::Duration::milliseconds(self.stint_paused_ms);
+        if let Some(paused_at) = self.paused_at {
+            paused += now - paused_at.max(started);
+        }
+        Some((now - started - paused).max(chrono::Duration::zero()))
+    }
+
+    /// Seconds played in the game with `exe_name`, including the stint in progress.
+    pub fn played_seconds(&self, exe_name: &str, now: DateTime<Utc>) -> u64 {
+        let mut played: u64 = self
+            .history
+            .iter()
+            .filter(|item| item.exe_name.eq_ignore_ascii_case(exe_name))
+            .map(|item| item.duration_seconds)
+            .sum();
+        if self
+            .current_exe
+            .as_deref()
+            .is_some_and(|exe| exe.eq_ignore_ascii_case(exe_name))
+        {
+            if let Some(stint) = self.stint_duration(now) {
+                played += stint.num_seconds().max(0) as u64;
+            }
+        }
+        played
+    }
+
+    /// Seconds left in the time budget of the game with `exe_name`, or `None`
+    /// if it has no budget.
+    pub fn budget_remaining(&self, exe_name: &str, now: DateTime<Utc>) -> Option<u64> {
+        let budget = *self.time_budgets.get(exe_name)?;
+        Some(budget.saturating_sub(self.played_seconds(exe_name, now)))
+    }
+
+    pub fn budget_used_up(&self, exe_name: &str, now: DateTime<Utc>) -> bool {
+        self.budget_remaining(exe_name, now) == Some(0)
+    }
+
+    pub fn is_completed(&self, exe_name: &str) -> bool {
+        self.completed
+            .iter()
+            .any(|c| c.exe_name.eq_ignore_ascii_case(exe_name))
+    }
+
+    /// Ends the current stint at `now`, adding it to the history and totals.
+    pub fn close_stint(&mut self, now: DateTime<Utc>) {
+        let (Some(game), Some(started_at), Some(stint)) = (
+            self.current_game.clone(),
+            self.last_swap_at,
+            self.stint_duration(now),
+        ) else {
+            return;
+        };
+        let duration = stint.num_seconds().max(0) as u64;
+        let paused = (now - started_at - stint).num_seconds().max(0) as u64;
+
+        self.history.push(SwapHistoryItem {
+            game_name: game.clone(),
+            exe_name: self.current_exe.clone().unwrap_or_default(),
+            started_at,
+            ended_at: now,
+            duration_seconds: duration,
+            paused_seconds: paused,
+            trigger: self.stint_trigger,
+        });
+        *self.total_times.entry(game).or_insert(0) += duration;
+    }
+
+    /// A copy to send to clients, with only the latest `RECENT_HISTORY` stints.
+    pub fn for_clients(&self) -> SwapState {
+        let mut state = self.clone();
+        let skip = state.history.len().saturating_sub(RECENT_HISTORY);
+        state.history.drain(..skip);
+        state
+    }
+
+    /// Closes the current stint and stops the countdown for good.
+    pub fn end_session(&mut self, now: DateTime<Utc>) {
+        self.close_stint(now);
+        self.current_game = None;
+        self.current_exe = None;
+        self.last_swap_at = None;
+        self.next_swap_at = None;
+        self.stint_paused_ms = 0;
+        self.queued_game = None;
+        self.queued_exe = None;
+        self.session_ended_at = Some(now);
+    }
+
+    /// Starts over as a new session, carrying the current game and pause
+    /// over into its first stint.
+    pub fn start_session(&mut self, id: i64, name: String, now: DateTime<Utc>) {
+        let mut fresh = SwapState {
+            current_game: self.current_game.take(),
+            current_exe: self.current_exe.take(),
+            session_id: Some(id),
+            session_name: Some(name),
+            ..Default::default()
+        };
+        if self.is_paused {
+            fresh.pause(now);
+        }
+        if fresh.current_game.is_some() {
+            fresh.start_stint(now, self.stint_trigger);
+        }
+        *self = fresh;
+    }
+
+    /// Time left on the countdown, which stays frozen while paused.
+    pub fn time_until_swap(&self, now: DateTime<Utc>) -> Option<chrono::Duration> {
+        let next = self.next_swap_at?;
+        Some(next - self.paused_at.unwrap_or(now))
+    }
+
+    fn refresh_timers(&mut self, now: DateTime<Utc>) {
+        self.history_len = self.history.len();
+        self.played_times.clear();
+        for item in &self.history {
+            *self.played_times.entry(item.exe_name.clone()).or_insert(0) += item.duration_seconds;
+        }
+        self.time_since_swap_seconds = self.stint_duration(now).map(|d| d.num_seconds());
+        self.time_until_swap_seconds = self.time_until_swap(now).map(|d| d.num_seconds());
+    }
 }
 
 
 #[derive(Clone)]
 pub struct StateBroadcaster {
     sender: broadcast::Sender<SwapState>,
+    events: broadcast::Sender<SessionEvent>,
 }
 
 impl StateBroadcaster {
     pub fn new() -> Self {
         let (sender, _) = broadcast::channel(16);
-        Self { sender }
+        let (events, _) = broadcast::channel(16);
+        Self { sender, events }
     }
 
     pub fn subscribe(&self) -> broadcast::Receiver<SwapState> {
@@ -48,6 +346,14 @@ impl StateBroadcaster {
     pub fn broadcast(&self, state: SwapState) {
         let _ = self.sender.send(state);
     }
+
+    pub fn subscribe_events(&self) -> broadcast::Receiver<SessionEvent> {
+        self.events.subscribe()
+    }
+
+    pub fn emit(&self, event: SessionEvent) {
+        let _ = self.events.send(event);
+    }
 }
 
 impl Default for StateBroadcaster {
@@ -59,13 +365,19 @@ impl Default for StateBroadcaster {
 pub struct AppState {
     pub swap_state: Arc<RwLock<SwapState>>,
     pub broadcaster: StateBroadcaster,
+    pub clock: Arc<dyn Clock>,
 }
 
 impl AppState {
     pub fn new() -> Self {
+        Self::with_clock(Arc::new(SystemClock))
+    }
+
+    pub fn with_clock(clock: Arc<dyn Clock>) -> Self {
         Self {
             swap_state: Arc::new(RwLock::new(SwapState::default())),
             broadcaster: StateBroadcaster::new(),
+            clock,
         }
     }
 
@@ -75,11 +387,8 @@ impl AppState {
     {
         let mut state = self.swap_state.write().await;
         f(&mut state);
-        
-        let now = Utc::now();
-        state.time_since_swap_seconds = state.last_swap_at.map(|t| (now - t).num_seconds());
-        state.time_until_swap_seconds = state.next_swap_at.map(|t| (t - now).num_seconds());
-        
+        state.refresh_timers(self.clock.now());
+
         let state_clone = state.clone();
         drop(state);
         self.broadcaster.broadcast(state_clone);
@@ -87,9 +396,23 @@ impl AppState {
 
     pub async fn get_state(&self) -> SwapState {
         let mut state = self.swap_state.read().await.clone();
-        let now = Utc::now();
-        state.time_since_swap_seconds = state.last_swap_at.map(|t| (now - t).num_seconds());
-        state.time_until_swap_seconds = state.next_swap_at.map(|t| (t - now).num_seconds());
+        state.refresh_timers(self.clock.now());
         state
     }
+
+    pub async fn pause(&self) {
+        let now = self.clock.now();
+        self.update_state(|s| s.pause(now)).await;
+    }
+
+    pub async fn resume(&self) {
+        let now = self.clock.now();
+        self.update_state(|s| s.resume(now)).await;
+    }
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        Self::new()
+    }
 }
@@ -0,0 +1,290 @@
+use crate::config::{AppConfig, SelectionStrategy};
+use crate::state::{SwapState, SwapTrigger};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// Play statistics for one game over the session.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GameStats {
+    pub game_name: String,
+    pub exe_name: String,
+    /// Number of stints, however the game was swapped in.
+    pub swap_ins: usize,
+    pub total_seconds: u64,
+    /// Fraction of the session's play time, from 0 to 1.
+    pub share: f64,
+    pub average_stint_seconds: f64,
+    pub shortest_stint_seconds: Option<u64>,
+    pub longest_stint_seconds: Option<u64>,
+    /// 0 while the game is on screen; `None` if it hasn't been played.
+    pub since_last_played_seconds: Option<u64>,
+    /// Longest time the game went unplayed between two of its stints, or
+    /// since its last one.
+    pub longest_gap_seconds: Option<u64>,
+    /// Automatic swaps that landed on this game.
+    pub random_picks: usize,
+    /// How many automatic picks the game should have got if every pick were fair.
+    pub expected_picks: f64,
+}
+
+/// How far the automatic picks are from what a fair pick would give.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Fairness {
+    pub strategy: SelectionStrategy,
+    pub random_picks: usize,
+    /// Pearson's chi-squared statistic of picks against expected picks.
+    pub chi_squared: f64,
+    pub degrees_of_freedom: usize,
+    /// Chance of a deviation at least this large from a fair pick. Small
+    /// values (below 0.05) mean the picks look skewed. `None` until there
+    /// are picks between at least two games.
+    pub p_value: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SessionStats {
+    pub generated_at: DateTime<Utc>,
+    pub total_seconds: u64,
+    pub swap_count: u64,
+    /// Most played first; enabled games that haven't been played come last.
+    pub games: Vec<GameStats>,
+    pub fairness: Fairness,
+}
+
+impl SessionStats {
+    pub fn new(state: &SwapState, config: &AppConfig, now: DateTime<Utc>) -> Self {
+        let mut state = state.clone();
+        state.close_stint(now);
+        let history = &state.history;
+
+        let mut games: Vec<GameStats> = Vec::new();
+        let names = config
+            .games
+            .iter()
+            .filter(|g| g.enabled)
+            .map(|g| (g.display_name.clone(), g.exe_name.clone()))
+            .chain(
+                history
+                    .iter()
+                    .map(|h| (h.game_name.clone(), h.exe_name.clone())),
+            );
+        for (game_name, exe_name) in names {
+            if !games.iter().any(|g| g.game_name == game_name) {
+                games.push(game_stats(&state, game_name, exe_name, now));
+            }
+        }
+
+        let total_seconds: u64 = history.iter().map(|h| h.duration_seconds).sum();
+        for game in &mut games {
+            if total_seconds > 0 {
+                game.share = game.total_seconds as f64 / total_seconds as f64;
+            }
+        }
+
+        let fairness = expected_picks(&state, config, &mut games);
+        games.sort_by(|a, b| {
+            b.total_seconds
+                .cmp(&a.total_seconds)
+                .then_with(|| b.swap_ins.cmp(&a.swap_ins))
+                .then_with(|| a.game_name.cmp(&b.game_name))
+        });
+
+        Self {
+            generated_at: now,
+            total_seconds,
+            swap_count: state.swap_count,
+            games,
+            fairness,
+        }
+    }
+}
+
+fn game_stats(
+    state: &SwapState,
+    game_name: String,
+    exe_name: String,
+    now: DateTime<Utc>,
+) -> GameStats {
+    let stints: Vec<_> = state
+        .history
+        .iter()
+        .filter(|h| h.game_name == game_name)
+        .collect();
+    let durations: Vec<u64> = stints.iter().map(|h| h.duration_seconds).collect();
+    let total_seconds: u64 = durations.iter().sum();
+
+    let since_last_played = stints
+        .last()
+        .map(|h| (now - h.ended_at).num_seconds().max(0) as u64);
+    let longest_gap = stints
+        .windows(2)
+        .map(|pair| (pair[1].started_at - pair[0].ended_at).num_seconds().max(0) as u64)
+        .chain(since_last_played)
+        .max();
+
+    GameStats {
+        game_name,
+        exe_name,
+        swap_ins: stints.len(),
+        total_seconds,
+        share: 0.0,
+        average_stint_seconds: if durations.is_empty() {
+            0.0
+        } else {
+            total_seconds as f64 / durations.len() as f64
+        },
+        shortest_stint_seconds: durations.iter().min().copied(),
+        longest_stint_seconds: durations.iter().max().copied(),
+        since_last_played_seconds: since_last_played,
+        longest_gap_seconds: longest_gap,
+        random_picks: 0,
+        expected_picks: 0.0,
+    }
+}
+
+/// Counts the automatic picks per game and what a fair pick would have
+/// given. Like the selector, a fair pick never repeats the game on screen and
+/// skips games completed by then; the weighted strategy favours games by
+/// their current `weight`.
+fn expected_picks(state: &SwapState, config: &AppConfig, games: &mut [GameStats]) -> Fairness {
+    let weighted = config.selection_strategy == SelectionStrategy::Weighted;
+    let weight_of = |name: &str| -> f64 {
+        let game = config
+            .games
+            .iter()
+            .find(|g| g.enabled && g.display_name == name);
+        match game {
+            Some(g) if weighted => g.weight.max(0.0),
+            Some(_) => 1.0,
+            None => 0.0,
+        }
+    };
+
+    let mut random_picks = 0;
+    for (i, stint) in state.history.iter().enumerate() {
+        if stint.trigger != SwapTrigger::Automatic {
+            continue;
+        }
+        let previous = i
+            .checked_sub(1)
+            .map(|p| state.history[p].game_name.as_str());
+        let eligible = |g: &GameStats| {
+            Some(g.game_name.as_str()) != previous
+                && !state
+                    .completed
+                    .iter()
+                    .any(|c| c.game_name == g.game_name && c.completed_at <= stint.started_at)
+        };
+
+        let total_weight: f64 = games
+            .iter()
+            .filter(|g| eligible(g))
+            .map(|g| weight_of(&g.game_name))
+            .sum();
+        if total_weight <= 0.0 {
+            continue;
+        }
+
+        random_picks += 1;
+        for game in games.iter_mut() {
+            if eligible(game) {
+                game.expected_picks += weight_of(&game.game_name) / total_weight;
+            }
+            if game.game_name == stint.game_name {
+                game.random_picks += 1;
+            }
+        }
+    }
+
+    let fair: Vec<&GameStats> = games.iter().filter(|g| g.expected_picks > 0.0).collect();
+    let chi_squared: f64 = fair
+        .iter()
+        .map(|g| {
+            let diff = g.random_picks as f64 - g.expected_picks;
+            diff * diff / g.expected_picks
+        })
+        .sum();
+    let degrees_of_freedom = fair.len().saturating_sub(1);
+    let p_value = (degrees_of_freedom > 0)
+        .then(|| upper_regularized_gamma(degrees_of_freedom as f64 / 2.0, chi_squared / 2.0));
+
+    Fairness {
+        strategy: config.selection_strategy,
+        random_picks,
+        chi_squared,
+        degrees_of_freedom,
+        p_value,
+    }
+}
+
+/// Q(a, x), the upper regularized incomplete gamma function, which gives the
+/// chi-squared p-value as Q(df / 2, chi² / 2).
+fn upper_regularized_gamma(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+
+    let ln_prefix = a * x.ln() - x - ln_gamma(a);
+    if x < a + 1.0 {
+        // Series for the lower function P(a, x)
+        let mut term = 1.0 / a;
+        let mut sum = term;
+        let mut n = a;
+        for _ in 0..500 {
+            n += 1.0;
+            term *= x / n;
+            sum += term;
+            if term.abs() < sum.abs() * 1e-15 {
+                break;
+            }
+        }
+        (1.0 - sum * ln_prefix.exp()).clamp(0.0, 1.0)
+    } else {
+        // Continued fraction for Q(a, x), by the modified Lentz method
+        let tiny = 1e-300;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..500 {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < tiny {
+                d = tiny;
+            }
+            c = b + an / c;
+            if c.abs() < tiny {
+                c = tiny;
+            }
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < 1e-15 {
+                break;
+            }
+        }
+        (ln_prefix.exp() * h).clamp(0.0, 1.0)
+    }
+}
+
+/// ln Γ(x) for x > 0, by the Lanczos approximation.
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.180_091_729_471_46,
+        -86.505_320_329_416_77,
+        24.014_098_240_830_91,
+        -1.231_739_572_450_155,
+        0.120_865_097_386_617_9e-2,
+        -0.539_523_938_495_3e-5,
+    ];
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+    let mut series = 1.000_000_000_190_015;
+    let mut y = x;
+    for c in COEFFICIENTS {
+        y += 1.0;
+        series += c / y;
+    }
+    -tmp + (2.506_628_274_631_000_5 * series / x).ln()
+}
@@ -1,72 +1,719 @@
-use crate::config::{AppConfig, GameConfig};
-use crate::input::send_esc;
-use crate::state::AppState;
-use crate::windows::{find_window_by_exe, focus_window};
+use crate::clock::Clock;
+use crate::config::{AppConfig, GameConfig, KeyDelivery, PauseMethod};
+use crate::db::SessionDb;
+use crate::duration::SwapDuration;
+use crate::input::{self, InputBackend, WindowInput};
+use crate::keys::KeySequence;
+use crate::process::Suspender;
+use crate::rng::{RngPosition, SwapRng};
+use crate::script::SwapScript;
+use crate::selection::{SelectionContext, Selector};
+use crate::session::SessionSnapshot;
+use crate::state::{
+    AppState, CompletedGame, ScheduledSwap, SessionEvent, SwapFailure, SwapState, SwapTrigger,
+};
+use crate::windows::WindowBackend;
 use anyhow::Result;
-use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use tokio::sync::RwLock;
-use tokio::time::sleep;
+use tokio::sync::{mpsc, oneshot, RwLock};
 use tracing::{error, info, warn};
 
+/// How soon a scripted swap that failed, e.g. because the game's window
+/// wasn't open, is tried again.
+const SCRIPT_RETRY_SECONDS: u64 = 5;
+
+/// How often focus is asked for again while waiting for a game to come to
+/// the front.
+const FOCUS_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Stint length bounds for `game`, falling back to the global ones.
+/// Overriding only one bound stretches the other so the override is honoured.
+pub fn stint_bounds(config: &AppConfig, game: Option<&GameConfig>) -> (SwapDuration, SwapDuration) {
+    let game_min = game.and_then(|g| g.min_swap);
+    let game_max = game.and_then(|g| g.max_swap);
+
+    match (game_min, game_max) {
+        (Some(min), Some(max)) => (min, max),
+        (Some(min), None) => (min, config.max_swap.max(min)),
+        (None, Some(max)) => (config.min_swap.min(max), max),
+        (None, None) => (config.min_swap, config.max_swap),
+    }
+}
+
+/// Picks the time until the next swap, in seconds, for a stint of `game`.
+/// Never less than a second, so a zero interval can't spin the swapper.
+pub fn calculate_delay<R: Rng + ?Sized>(
+    config: &AppConfig,
+    game: Option<&GameConfig>,
+    rng: &mut R,
+) -> u64 {
+    let (min, max) = stint_bounds(config, game);
+    let min = min.as_secs().max(1);
+    let max = max.as_secs().max(1);
+    if min >= max {
+        return max;
+    }
+    rng.gen_range(min..=max)
+}
+
+/// Control actions handled by the `run` loop.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SwapCommand {
+    /// Throw away the pending timer and roll a new delay.
+    Reschedule,
+    /// Swap right now, optionally to the game with this exe name, and restart the timer.
+    ForceSwap(Option<String>),
+    /// Pin the next pick to the game with this exe name, or clear the pin with `None`.
+    QueueNext(Option<String>),
+    Pause,
+    Resume,
+    /// The config was saved; reschedule if the timing settings changed.
+    ConfigChanged,
+    /// Move the pending swap later (positive) or earlier (negative) by this many seconds.
+    AdjustTimer(i64),
+    /// Set the time until the pending swap, in seconds.
+    SetTimer(u64),
+    /// Mark the game with this exe name as beaten and retire it from the rotation.
+    Complete(String),
+    /// Replace the session with a saved one, keeping its countdown.
+    Restore(Box<SessionSnapshot>),
+    /// End the session and start a new one, recorded under this database id.
+    NewSession {
+        id: i64,
+        name: String,
+    },
+    /// Move the scheduled swap with this id to position `to` in the schedule.
+    MoveScheduled {
+        id: u64,
+        to: usize,
+    },
+    /// Pick a new game and duration for the scheduled swap with this id, or
+    /// plan the whole schedule again with `None`.
+    RerollScheduled(Option<u64>),
+}
+
+type CommandRequest = (SwapCommand, oneshot::Sender<Result<()>>);
+
+/// The config values the pending timer was rolled with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TimingSettings {
+    min_swap: SwapDuration,
+    max_swap: SwapDuration,
+    time_budget: Option<SwapDuration>,
+    auto_swap_enabled: bool,
+}
+
+impl TimingSettings {
+    fn from_config(config: &AppConfig, game: Option<&GameConfig>) -> Self {
+        let (min_swap, max_swap) = stint_bounds(config, game);
+        Self {
+            min_swap,
+            max_swap,
+            time_budget: game.and_then(|g| g.time_budget),
+            auto_swap_enabled: config.auto_swap_enabled,
+        }
+    }
+}
+
+/// Looks up an enabled game by exe name.
+fn find_game<'a>(games: &[&'a GameConfig], exe_name: &str) -> Result<&'a GameConfig> {
+    games
+        .iter()
+        .find(|g| g.exe_name.eq_ignore_ascii_case(exe_name))
+        .copied()
+        .ok_or_else(|| anyhow::anyhow!("{} is not an enabled game in the rotation", exe_name))
+}
+
+/// Errors if `game` has left the rotation for the rest of the session.
+fn check_in_rotation(state: &SwapState, game: &GameConfig, now: DateTime<Utc>) -> Result<()> {
+    if state.is_completed(&game.exe_name) {
+        anyhow::bail!("{} is already completed", game.display_name);
+    }
+    if state.budget_used_up(&game.exe_name, now) {
+        anyhow::bail!("{} has used up its time budget", game.display_name);
+    }
+    Ok(())
+}
+
+/// Finds the game a swap or queue request names, and errors if it can't be
+/// picked next: not enabled, already playing, or out of the rotation.
+fn find_target<'a>(
+    games: &[&'a GameConfig],
+    state: &SwapState,
+    exe_name: &str,
+    now: DateTime<Utc>,
+) -> Result<&'a GameConfig> {
+    let game = find_game(games, exe_name)?;
+    if state
+        .current_exe
+        .as_deref()
+        .is_some_and(|exe| exe.eq_ignore_ascii_case(&game.exe_name))
+    {
+        anyhow::bail!("{} is already the current game", game.display_name);
+    }
+    check_in_rotation(state, game, now)?;
+    Ok(game)
+}
+
+/// A swap was asked for a game that can't be swapped to, as opposed to a
+/// swap that went wrong on the way.
+#[derive(Debug)]
+pub struct InvalidTarget(pub String);
+
+impl fmt::Display for InvalidTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for InvalidTarget {}
+
 pub struct Swapper {
     config: Arc<RwLock<AppConfig>>,
     app_state: Arc<AppState>,
+    windows: Arc<dyn WindowBackend>,
+    input: Arc<dyn InputBackend>,
+    clock: Arc<dyn Clock>,
+    commands: mpsc::UnboundedSender<CommandRequest>,
+    receiver: Mutex<Option<mpsc::UnboundedReceiver<CommandRequest>>>,
+    selector: Mutex<Selector>,
+    rng: Mutex<SwapRng>,
+    /// Stint length of a scheduled swap that was just taken, for `schedule_next`.
+    planned_delay: Mutex<Option<u64>>,
+    db: Option<Arc<SessionDb>>,
+    script: Option<SwapScript>,
+    suspender: Option<Arc<Suspender>>,
 }
 
 impl Swapper {
-    pub fn new(config: Arc<RwLock<AppConfig>>, app_state: Arc<AppState>) -> Self {
-        Self { config, app_state }
+    pub fn new(
+        config: Arc<RwLock<AppConfig>>,
+        app_state: Arc<AppState>,
+        windows: Arc<dyn WindowBackend>,
+        input: Arc<dyn InputBackend>,
+    ) -> Self {
+        let clock = app_state.clock.clone();
+        let (commands, receiver) = mpsc::unbounded_channel();
+        Self {
+            config,
+            app_state,
+            windows,
+            input,
+            clock,
+            commands,
+            receiver: Mutex::new(Some(receiver)),
+            selector: Mutex::new(Selector::new()),
+            rng: Mutex::new(SwapRng::from_entropy()),
+            planned_delay: Mutex::new(None),
+            db: None,
+            script: None,
+            suspender: None,
+        }
+    }
+
+    /// Records every stint and completion of the session in `db`.
+    pub fn with_session_db(mut self, db: Arc<SessionDb>) -> Self {
+        self.db = Some(db);
+        self
+    }
+
+    /// Plays `script` back instead of picking games and stint lengths.
+    pub fn with_script(mut self, script: SwapScript) -> Self {
+        self.script = Some(script);
+        self
+    }
+
+    /// Pauses games set to `PauseMethod::Suspend` by suspending their process
+    /// with `suspender`.
+    pub fn with_suspender(mut self, suspender: Arc<Suspender>) -> Self {
+        self.suspender = Some(suspender);
+        self
+    }
+
+    pub fn is_scripted(&self) -> bool {
+        self.script.is_some()
+    }
+
+    /// Sends a command to the `run` loop and waits for it to be applied.
+    pub async fn send(&self, command: SwapCommand) -> Result<()> {
+        let (done, result) = oneshot::channel();
+        self.commands
+            .send((command, done))
+            .map_err(|_| anyhow::anyhow!("Swapper is not running"))?;
+        result
+            .await
+            .map_err(|_| anyhow::anyhow!("Swapper stopped before handling the command"))?
+    }
+
+    pub async fn force_swap(&self) -> Result<()> {
+        self.send(SwapCommand::ForceSwap(None)).await
+    }
+
+    pub async fn swap_to(&self, exe_name: &str) -> Result<()> {
+        self.send(SwapCommand::ForceSwap(Some(exe_name.to_string())))
+            .await
+    }
+
+    pub async fn queue_next(&self, exe_name: Option<&str>) -> Result<()> {
+        self.send(SwapCommand::QueueNext(exe_name.map(|e| e.to_string())))
+            .await
+    }
+
+    pub async fn pause(&self) -> Result<()> {
+        self.send(SwapCommand::Pause).await
+    }
+
+    pub async fn resume(&self) -> Result<()> {
+        self.send(SwapCommand::Resume).await
+    }
+
+    pub async fn reschedule(&self) -> Result<()> {
+        self.send(SwapCommand::Reschedule).await
+    }
+
+    pub async fn config_changed(&self) -> Result<()> {
+        self.send(SwapCommand::ConfigChanged).await
+    }
+
+    pub async fn adjust_timer(&self, seconds: i64) -> Result<()> {
+        self.send(SwapCommand::AdjustTimer(seconds)).await
+    }
+
+    pub async fn set_timer(&self, seconds: u64) -> Result<()> {
+        self.send(SwapCommand::SetTimer(seconds)).await
+    }
+
+    pub async fn complete(&self, exe_name: &str) -> Result<()> {
+        self.send(SwapCommand::Complete(exe_name.to_string())).await
+    }
+
+    pub async fn restore(&self, snapshot: SessionSnapshot) -> Result<()> {
+        self.send(SwapCommand::Restore(Box::new(snapshot))).await
+    }
+
+    pub async fn move_scheduled(&self, id: u64, to: usize) -> Result<()> {
+        self.send(SwapCommand::MoveScheduled { id, to }).await
+    }
+
+    pub async fn reroll_scheduled(&self, id: Option<u64>) -> Result<()> {
+        self.send(SwapCommand::RerollScheduled(id)).await
+    }
+
+    pub async fn new_session(&self, id: i64, name: &str) -> Result<()> {
+        self.send(SwapCommand::NewSession {
+            id,
+            name: name.to_string(),
+        })
+        .await
     }
 
     pub async fn run(&self) {
+        let Some(mut commands) = self.receiver.lock().unwrap().take() else {
+            error!("Swapper is already running");
+            return;
+        };
+
+        self.sync_budgets().await;
+        self.sync_rng(false).await;
+        let bag = self.app_state.get_state().await.shuffle_bag;
+        self.selector.lock().unwrap().restore_bag(bag);
+        let mut timing = self.resume_schedule().await;
+
         loop {
+            self.fill_schedule().await;
+            self.sync_db().await;
             let state = self.app_state.get_state().await;
+            let deadline = state.next_swap_at.filter(|_| !state.is_paused);
+            let remaining = deadline
+                .and_then(|d| (d - self.clock.now()).to_std().ok())
+                .unwrap_or_default();
 
-            if state.is_paused {
-                sleep(Duration::from_secs(1)).await;
-                continue;
-            }
+            tokio::select! {
+                _ = self.clock.sleep(remaining), if deadline.is_some() => {
+                    if let Err(e) = self.do_swap(None, SwapTrigger::Automatic).await {
+                        error!("Swap failed: {:?}", e);
+                    }
+                    timing = self.schedule_next().await;
+                }
+                request = commands.recv() => {
+                    let Some((command, done)) = request else {
+                        return;
+                    };
 
-            let config = self.config.read().await;
+                    let result = match command {
+                        SwapCommand::ForceSwap(target) => {
+                            let result = self.do_swap(target.as_deref(), SwapTrigger::Forced).await;
+                            timing = self.schedule_next().await;
+                            result
+                        }
+                        SwapCommand::QueueNext(exe_name) => self.queue_next_game(exe_name).await,
+                        SwapCommand::Pause => {
+                            self.app_state.pause().await;
+                            Ok(())
+                        }
+                        SwapCommand::Resume => {
+                            self.app_state.resume().await;
+                            Ok(())
+                        }
+                        SwapCommand::Reschedule => {
+                            timing = self.schedule_next().await;
+                            Ok(())
+                        }
+                        SwapCommand::ConfigChanged => {
+                            self.release_suspended(&*self.config.read().await);
+                            self.sync_budgets().await;
+                            let reseeded = self.sync_rng(false).await;
+                            let current = self.current_timing().await;
+                            // Scripted stints don't depend on the swap range
+                            let changed = match self.script {
+                                Some(_) => current.auto_swap_enabled != timing.auto_swap_enabled,
+                                None => current != timing || reseeded,
+                            };
+                            if changed {
+                                info!("Swap timing changed, rescheduling");
+                                timing = self.schedule_next().await;
+                            }
+                            Ok(())
+                        }
+                        SwapCommand::AdjustTimer(seconds) => {
+                            self.move_deadline(|base, next| {
+                                (next + chrono::Duration::seconds(seconds)).max(base)
+                            })
+                            .await
+                        }
+                        SwapCommand::SetTimer(seconds) => {
+                            self.move_deadline(|base, _| {
+                                base + chrono::Duration::seconds(seconds as i64)
+                            })
+                            .await
+                        }
+                        SwapCommand::Restore(snapshot) => {
+                            let mut state = snapshot.into_state(self.clock.now());
+                            let current = self.app_state.get_state().await;
+                            if state.session_id.is_none() {
+                                // Saved before sessions were recorded; carry on under the current one
+                                state.session_id = current.session_id;
+                                state.session_name = current.session_name;
+                            } else if let (Some(db), Some(id)) = (self.db.clone(), current.session_id) {
+                                if state.session_id != Some(id) {
+                                    let now = self.clock.now();
+                                    // Only a session that saw some play is kept
+                                    let result = tokio::task::spawn_blocking(move || {
+                                        if current.history.is_empty() && current.completed.is_empty() {
+                                            db.remove_if_empty(id)
+                                        } else {
+                                            db.finish(&current, now)
+                                        }
+                                    })
+                                    .await;
+                                    if let Err(e) =
+                                        result.map_err(anyhow::Error::from).and_then(|r| r)
+                                    {
+                                        error!("Failed to close replaced session: {:?}", e);
+                                    }
+                                }
+                            }
+                            info!("Restored session with {} swaps", state.swap_count);
+                            self.resume_all();
+                            self.selector
+                                .lock()
+                                .unwrap()
+                                .restore_bag(state.shuffle_bag.clone());
+                            self.app_state.update_state(|s| *s = state).await;
+                            self.sync_budgets().await;
+                            self.sync_rng(false).await;
+                            timing = self.resume_schedule().await;
+                            Ok(())
+                        }
+                        SwapCommand::NewSession { id, name } => {
+                            let now = self.clock.now();
+                            // Close the old session first so its last stint is recorded
+                            self.app_state
+                                .update_state(|s| {
+                                    s.close_stint(now);
+                                    s.session_ended_at = Some(now);
+                                })
+                                .await;
+                            self.sync_db().await;
+                            info!("Started session {:?}", name);
+                            self.app_state
+                                .update_state(|s| s.start_session(id, name, now))
+                                .await;
+                            self.selector.lock().unwrap().reset();
+                            self.sync_budgets().await;
+                            self.sync_rng(true).await;
+                            timing = self.schedule_next().await;
+                            Ok(())
+                        }
+                        SwapCommand::MoveScheduled { id, to } => self.move_scheduled_swap(id, to).await,
+                        SwapCommand::RerollScheduled(id) => {
+                            let result = self.reroll_scheduled_swap(id).await;
+                            self.fill_schedule().await;
+                            result
+                        }
+                        SwapCommand::Complete(exe_name) => match self.complete_game(&exe_name).await {
+                            Ok(true) => {
+                                let result = self.do_swap(None, SwapTrigger::Forced).await;
+                                timing = self.schedule_next().await;
+                                result
+                            }
+                            Ok(false) => Ok(()),
+                            Err(e) => Err(e),
+                        },
+                    };
 
-            if !config.auto_swap_enabled {
-                drop(config);
-                sleep(Duration::from_secs(1)).await;
-                continue;
+                    let _ = done.send(result);
+                }
             }
+        }
+    }
 
-            let delay_seconds = self.calculate_delay(&config);
-            drop(config);
+    /// Writes anything new in the session to the session database.
+    async fn sync_db(&self) {
+        let Some(db) = self.db.clone() else {
+            return;
+        };
+        let state = self.app_state.get_state().await;
+        let result = tokio::task::spawn_blocking(move || db.sync(&state)).await;
+        if let Err(e) = result.map_err(anyhow::Error::from).and_then(|r| r) {
+            error!("Failed to record session: {:?}", e);
+        }
+    }
 
-            let next_swap = chrono::Utc::now() + chrono::Duration::seconds(delay_seconds as i64);
-            self.app_state
-                .update_state(|s| s.next_swap_at = Some(next_swap))
-                .await;
+    /// Seeds the RNG from the config, or keeps the session's own seed and
+    /// position so a resumed session carries on with the same draws. Without
+    /// a configured seed, a `fresh` session gets a new random one. Returns
+    /// whether the RNG was reseeded.
+    async fn sync_rng(&self, fresh: bool) -> bool {
+        let configured = self.config.read().await.seed;
+        let state = self.app_state.get_state().await;
+        let current = state.seed.filter(|_| !fresh);
 
-            sleep(Duration::from_secs(delay_seconds)).await;
+        let rng = match (configured, current) {
+            (Some(seed), Some(current)) if seed == current => SwapRng::at(seed, state.rng_position),
+            (Some(seed), _) => SwapRng::new(seed),
+            (None, Some(current)) => SwapRng::at(current, state.rng_position),
+            (None, None) => SwapRng::from_entropy(),
+        };
+        let reseeded = current != Some(rng.seed());
+        if reseeded {
+            info!("Session seed: {}", rng.seed());
+        }
 
-            if self.app_state.get_state().await.is_paused {
-                continue;
-            }
+        let (seed, position) = (rng.seed(), rng.position());
+        *self.rng.lock().unwrap() = rng;
+        self.app_state
+            .update_state(|s| {
+                s.seed = Some(seed);
+                s.rng_position = position;
+            })
+            .await;
+        reseeded
+    }
 
-            if let Err(e) = self.do_swap().await {
-                error!("Swap failed: {:?}", e);
-            }
+    /// Where the seeded picks are at, for the state: the RNG position and
+    /// the shuffle bag.
+    fn draws(&self) -> (RngPosition, Vec<String>) {
+        let position = self.rng.lock().unwrap().position();
+        let bag = self.selector.lock().unwrap().bag().to_vec();
+        (position, bag)
+    }
+
+    /// Copies the enabled games' time budgets from the config into the state.
+    async fn sync_budgets(&self) {
+        let budgets = self.config.read().await.time_budgets();
+        self.app_state
+            .update_state(|s| s.time_budgets = budgets)
+            .await;
+    }
+
+    /// Rolls a new delay and sets `next_swap_at`, or clears it when auto swap is off.
+    /// While paused the delay counts from the moment the pause started. A game
+    /// with a time budget is always swapped out before it goes over.
+    async fn schedule_next(&self) -> TimingSettings {
+        let state = self.app_state.get_state().await;
+        let config = self.config.read().await;
+        let game = state
+            .current_exe
+            .as_deref()
+            .and_then(|exe| config.game_by_exe(exe));
+        let timing = TimingSettings::from_config(&config, game);
+        let planned = self.planned_delay.lock().unwrap().take().or_else(|| {
+            // Rescheduling a scripted stint starts it over
+            let played = state.script_position.checked_sub(1)?;
+            Some(self.script.as_ref()?.entry(played)?.duration.as_secs())
+        });
+        let delay_seconds = config.auto_swap_enabled.then(|| {
+            planned.unwrap_or_else(|| {
+                calculate_delay(&config, game, self.rng.lock().unwrap().delays())
+            })
+        });
+        let now = self.clock.now();
+        let budget_left = game.and_then(|g| state.budget_remaining(&g.exe_name, now));
+        drop(config);
+
+        let delay_seconds = match (delay_seconds, budget_left) {
+            (Some(delay), Some(left)) => Some(delay.min(left)),
+            (delay, left) => delay.or(left),
         }
+        .filter(|_| state.session_ended_at.is_none());
+
+        let base = state.paused_at.unwrap_or(now);
+        let next_swap = delay_seconds.map(|d| base + chrono::Duration::seconds(d as i64));
+
+        let (position, bag) = self.draws();
+        self.app_state
+            .update_state(|s| {
+                s.next_swap_at = next_swap;
+                s.rng_position = position;
+                s.shuffle_bag = bag;
+            })
+            .await;
+        timing
     }
 
-    fn calculate_delay(&self, config: &AppConfig) -> u64 {
-        use rand::Rng;
-        let min = config.min_swap_minutes * 60;
-        let max = config.max_swap_minutes * 60;
-        if min >= max {
-            return max as u64;
+    /// Keeps a countdown carried over from a restored session, or rolls a new one.
+    async fn resume_schedule(&self) -> TimingSettings {
+        let state = self.app_state.get_state().await;
+        if state.next_swap_at.is_some() {
+            return self.current_timing().await;
+        }
+        if self.script.is_some() && state.current_exe.is_none() {
+            // A script starts with its first game rather than a random wait
+            *self.planned_delay.lock().unwrap() = Some(0);
         }
-        rand::thread_rng().gen_range(min..=max) as u64
+        self.schedule_next().await
     }
 
-    async fn do_swap(&self) -> Result<()> {
+    /// Timing settings that would apply to the current stint right now.
+    async fn current_timing(&self) -> TimingSettings {
+        let current_exe = self.app_state.get_state().await.current_exe;
+        let config = self.config.read().await;
+        let game = current_exe.and_then(|exe| config.game_by_exe(&exe));
+        TimingSettings::from_config(&config, game)
+    }
+
+    /// Replaces the pending `next_swap_at`. The closure gets the moment the
+    /// countdown is measured from (now, or when the pause started) and the
+    /// current deadline. The result is capped at the current game's time budget.
+    async fn move_deadline<F>(&self, f: F) -> Result<()>
+    where
+        F: FnOnce(DateTime<Utc>, DateTime<Utc>) -> DateTime<Utc>,
+    {
+        let state = self.app_state.get_state().await;
+        let Some(next_swap) = state.next_swap_at else {
+            anyhow::bail!("No swap is scheduled");
+        };
+
+        let now = self.clock.now();
+        let base = state.paused_at.unwrap_or(now);
+        let mut next_swap = f(base, next_swap);
+        let budget_left = state
+            .current_exe
+            .as_deref()
+            .and_then(|exe| state.budget_remaining(exe, now));
+        if let Some(left) = budget_left {
+            next_swap = next_swap.min(base + chrono::Duration::seconds(left as i64));
+        }
+        info!("Next swap moved to {}", next_swap);
+
+        self.app_state
+            .update_state(|s| s.next_swap_at = Some(next_swap))
+            .await;
+        Ok(())
+    }
+
+    async fn queue_next_game(&self, exe_name: Option<String>) -> Result<()> {
+        let queued = match exe_name {
+            Some(exe_name) => {
+                let config = self.config.read().await;
+                let enabled_games: Vec<&GameConfig> =
+                    config.games.iter().filter(|g| g.enabled).collect();
+                let state = self.app_state.get_state().await;
+                let game = find_target(&enabled_games, &state, &exe_name, self.clock.now())?;
+
+                info!("Queued next game: {}", game.display_name);
+                Some((game.display_name.clone(), game.exe_name.clone()))
+            }
+            None => {
+                info!("Cleared queued game");
+                None
+            }
+        };
+
+        self.app_state
+            .update_state(|s| {
+                s.queued_game = queued.as_ref().map(|(name, _)| name.clone());
+                s.queued_exe = queued.map(|(_, exe)| exe);
+            })
+            .await;
+        Ok(())
+    }
+
+    /// Records `exe_name` as completed and announces it. Returns whether the
+    /// swapper has to move on, because the game was active or nothing is left.
+    async fn complete_game(&self, exe_name: &str) -> Result<bool> {
+        let config = self.config.read().await;
+        let enabled_games: Vec<&GameConfig> = config.games.iter().filter(|g| g.enabled).collect();
+        let game = find_game(&enabled_games, exe_name)?;
+
+        let state = self.app_state.get_state().await;
+        if state.is_completed(&game.exe_name) {
+            anyhow::bail!("{} is already completed", game.display_name);
+        }
+
+        let now = self.clock.now();
+        let completed = CompletedGame {
+            game_name: game.display_name.clone(),
+            exe_name: game.exe_name.clone(),
+            completed_at: now,
+            total_seconds: state.played_seconds(&game.exe_name, now),
+        };
+        self.resume_game(game);
+        let is_current = state
+            .current_exe
+            .as_deref()
+            .is_some_and(|exe| exe.eq_ignore_ascii_case(&game.exe_name));
+        info!(
+            "Completed {} after {}s",
+            completed.game_name, completed.total_seconds
+        );
+
+        self.app_state
+            .update_state(|s| {
+                if is_current {
+                    // The stint ends now, not when the swap away lands
+                    s.close_stint(now);
+                    s.last_swap_at = None;
+                    s.stint_paused_ms = 0;
+                }
+                if s.queued_exe
+                    .as_deref()
+                    .is_some_and(|exe| exe.eq_ignore_ascii_case(&completed.exe_name))
+                {
+                    s.queued_game = None;
+                    s.queued_exe = None;
+                }
+                s.completed.push(completed.clone());
+            })
+            .await;
+        self.app_state
+            .broadcaster
+            .emit(SessionEvent::GameCompleted(completed));
+
+        let state = self.app_state.get_state().await;
+        let nothing_left = enabled_games
+            .iter()
+            .all(|g| check_in_rotation(&state, g, now).is_err());
+        Ok(is_current || nothing_left)
+    }
+
+    async fn do_swap(&self, target: Option<&str>, trigger: SwapTrigger) -> Result<()> {
         let config = self.config.read().await;
         let enabled_games: Vec<&GameConfig> = config.games.iter().filter(|g| g.enabled).collect();
 
@@ -75,72 +722,193 @@ impl Swapper {
             return Ok(());
         }
 
-        let current_exe = self.app_state.get_state().await.current_exe.clone();
+        let state = self.app_state.get_state().await;
+        let current_exe = state.current_exe.clone();
+        let now = self.clock.now();
+
+        let target = match target {
+            Some(exe) => Some(
+                find_target(&enabled_games, &state, exe, now)
+                    .map_err(|e| InvalidTarget(e.to_string()))?,
+            ),
+            None => None,
+        };
+
+        let in_rotation: Vec<&GameConfig> = enabled_games
+            .iter()
+            .filter(|g| check_in_rotation(&state, g, now).is_ok())
+            .copied()
+            .collect();
+
+        if in_rotation.is_empty() {
+            if state.session_ended_at.is_some() {
+                anyhow::bail!("No games left in the rotation");
+            }
+            info!("Every game is completed or out of time, ending the session");
+            self.app_state.update_state(|s| s.end_session(now)).await;
+            self.resume_all();
+            return Ok(());
+        }
+
+        let queued_game = state.queued_exe.as_deref().and_then(|exe| {
+            let game = find_game(&in_rotation, exe);
+            if let Err(e) = &game {
+                warn!("Ignoring queued game: {}", e);
+            }
+            game.ok()
+        });
 
-        let next_game = self.find_next_game(&enabled_games, current_exe.as_deref())?;
+        let scripted = match (&self.script, target, queued_game) {
+            (Some(script), None, None) => {
+                // Entries for games that left the rotation are skipped, at
+                // most once around a looping script
+                let mut position = state.script_position;
+                let found = loop {
+                    let Some(entry) = script.entry(position) else {
+                        break None;
+                    };
+                    if position - state.script_position >= script.entries.len() as u64 {
+                        break None;
+                    }
+                    match find_game(&in_rotation, &entry.exe_name) {
+                        Ok(game) => break Some((entry, game)),
+                        Err(_) => {
+                            warn!(
+                                "Skipping {} in the script, it's not in the rotation",
+                                entry.game_name
+                            );
+                            position += 1;
+                        }
+                    }
+                };
+                if position != state.script_position {
+                    self.app_state
+                        .update_state(|s| s.script_position = position)
+                        .await;
+                }
+
+                let Some(found) = found else {
+                    match script.entry(position) {
+                        Some(_) => info!("No game in the script is left, ending the session"),
+                        None => info!("The script is over, ending the session"),
+                    }
+                    self.app_state.update_state(|s| s.end_session(now)).await;
+                    self.resume_all();
+                    return Ok(());
+                };
+                // Replaced by the entry's duration once the swap goes through
+                *self.planned_delay.lock().unwrap() = Some(SCRIPT_RETRY_SECONDS);
+                Some(found)
+            }
+            _ => None,
+        };
+
+        let scheduled = match (target, queued_game, scripted) {
+            (None, None, None) => self.take_scheduled(&in_rotation, &state),
+            _ => None,
+        };
+
+        let (next_game, trigger) = match (target, queued_game, scripted, &scheduled) {
+            (Some(game), ..) => (game, trigger),
+            (None, Some(game), ..) if trigger == SwapTrigger::Automatic => {
+                (game, SwapTrigger::Queued)
+            }
+            (None, Some(game), ..) => (game, trigger),
+            (None, None, Some((_, game)), _) => match trigger {
+                SwapTrigger::Automatic => (game, SwapTrigger::Scripted),
+                _ => (game, trigger),
+            },
+            (None, None, None, Some((game, _))) => (*game, trigger),
+            (None, None, None, None) => {
+                (self.find_next_game(&config, &in_rotation, &state)?, trigger)
+            }
+        };
 
         info!("Swapping to: {}", next_game.display_name);
 
         let current_config = current_exe
-            .and_then(|exe| config.games.iter().find(|g| g.exe_name == exe));
+            .as_deref()
+            .and_then(|exe| config.game_by_exe(exe));
 
         if let Some(current) = current_config {
-            if current.send_esc_on_leave {
-                info!("Sending ESC to leave: {}", current.display_name);
-                if let Err(e) = send_esc() {
-                    warn!("Failed to send ESC: {:?}", e);
-                }
-            }
+            self.leave_game(current).await;
         }
 
-        sleep(Duration::from_millis(100)).await;
+        self.clock.sleep(Duration::from_millis(100)).await;
 
-        if let Some(hwnd) = find_window_by_exe(&next_game.exe_name) {
+        if let Some(hwnd) = self.windows.find_window_by_exe(&next_game.exe_name) {
             info!("Found window handle: {}", hwnd);
+            self.resume_game(next_game);
             
-            if let Err(e) = focus_window(hwnd) {
-                warn!("Failed to focus window: {:?}", e);
-                anyhow::bail!("Failed to focus window: {:?}", e);
+            if let Err(e) = self.focus_game(hwnd, next_game, &config).await {
+                warn!("{:?}", e);
+                self.app_state
+                    .broadcaster
+                    .emit(SessionEvent::SwapFailed(SwapFailure {
+                        game_name: next_game.display_name.clone(),
+                        exe_name: next_game.exe_name.clone(),
+                        reason: e.to_string(),
+                        failed_at: self.clock.now(),
+                    }));
+                // It isn't swapped in, so it stays paused
+                if next_game.pause_method == PauseMethod::Suspend
+                    && !current_exe
+                        .as_deref()
+                        .is_some_and(|exe| exe.eq_ignore_ascii_case(&next_game.exe_name))
+                {
+                    self.suspend_game(next_game);
+                }
+                if let Some(current) = current_config {
+                    self.return_to(current, &config).await;
+                }
+                return Err(e);
             }
 
-            sleep(Duration::from_millis(100)).await;
-
-            if next_game.send_esc_on_enter {
-                info!("Sending ESC to enter: {}", next_game.display_name);
-                if let Err(e) = send_esc() {
-                    warn!("Failed to send ESC: {:?}", e);
-                }
+            if next_game.pause_method == PauseMethod::Keys {
+                self.send_keys(next_game, &next_game.on_enter, "enter")
+                    .await;
             }
 
-            let now = chrono::Utc::now();
-            let state = self.app_state.get_state().await;
-            
-            let mut new_history = state.history.clone();
-            let mut total_times = state.total_times.clone();
-            if let (Some(prev_game), Some(prev_time)) = (state.current_game, state.last_swap_at) {
-                let duration = (now - prev_time).num_seconds().max(0) as u64;
-                new_history.insert(0, crate::state::SwapHistoryItem {
-                    game_name: prev_game.clone(),
-                    duration_seconds: duration,
-                });
-                
-                *total_times.entry(prev_game).or_insert(0) += duration;
-                
-                if new_history.len() > 10 {
-                    new_history.pop();
-                }
+            let now = self.clock.now();
+            // A scheduled game was drawn from the shuffle bag when it was planned
+            if scheduled.is_none() && scripted.is_none() {
+                self.selector.lock().unwrap().played(&next_game.exe_name);
+            }
+            let (position, bag) = self.draws();
+            let consumed = scheduled.as_ref().map(|(_, entry)| {
+                *self.planned_delay.lock().unwrap() = Some(entry.duration_seconds);
+                entry.id
+            });
+            if let Some((entry, _)) = scripted {
+                *self.planned_delay.lock().unwrap() = Some(entry.duration.as_secs());
             }
-            
-            let swap_count = state.swap_count + 1;
 
             self.app_state
                 .update_state(|s| {
+                    s.close_stint(now);
                     s.current_game = Some(next_game.display_name.clone());
                     s.current_exe = Some(next_game.exe_name.clone());
-                    s.last_swap_at = Some(now);
-                    s.swap_count = swap_count;
-                    s.history = new_history;
-                    s.total_times = total_times;
+                    s.start_stint(now, trigger);
+                    s.swap_count += 1;
+                    s.session_ended_at = None;
+                    s.rng_position = position;
+                    s.shuffle_bag = bag;
+                    if scripted.is_some() {
+                        s.script_position += 1;
+                    }
+                    if let Some(id) = consumed {
+                        // Entries skipped on the way were for games no longer available
+                        if let Some(index) = s.schedule.iter().position(|e| e.id == id) {
+                            s.schedule.drain(..=index);
+                        }
+                    }
+                    if s.queued_exe
+                        .as_deref()
+                        .is_some_and(|exe| exe.eq_ignore_ascii_case(&next_game.exe_name))
+                    {
+                        s.queued_game = None;
+                        s.queued_exe = None;
+                    }
                 })
                 .await;
 
@@ -162,42 +930,461 @@ impl Swapper {
                 }
             }
         } else {
+            if let Some(current) = current_config {
+                self.return_to(current, &config).await;
+            }
+            if target.is_some() {
+                anyhow::bail!("Game window not found: {}", next_game.exe_name);
+            }
             warn!("Game window not found: {}", next_game.exe_name);
         }
 
         Ok(())
     }
 
+    /// Pauses `game` before swapping away from it, with keys or by suspending
+    /// its process.
+    async fn leave_game(&self, game: &GameConfig) {
+        match game.pause_method {
+            PauseMethod::Keys => self.send_keys(game, &game.on_leave, "leave").await,
+            PauseMethod::Suspend => self.suspend_game(game),
+        }
+    }
+
+    /// Undoes `leave_game` when the swap away from `game` failed, so the game
+    /// that stays on screen isn't left paused.
+    async fn return_to(&self, game: &GameConfig, config: &AppConfig) {
+        match game.pause_method {
+            // Nothing was sent, so there's nothing to undo
+            PauseMethod::Keys if game.on_leave.is_empty() => {}
+            PauseMethod::Keys => {
+                let Some(hwnd) = self.windows.find_window_by_exe(&game.exe_name) else {
+                    warn!("{} has no window, it stays paused", game.display_name);
+                    return;
+                };
+                match self.focus_game(hwnd, game, config).await {
+                    Ok(()) => self.send_keys(game, &game.on_enter, "enter").await,
+                    Err(e) => warn!("{} stays paused: {:?}", game.display_name, e),
+                }
+            }
+            PauseMethod::Suspend => self.resume_game(game),
+        }
+    }
+
+    fn suspend_game(&self, game: &GameConfig) {
+        let Some(suspender) = &self.suspender else {
+            warn!(
+                "Processes can't be suspended here, {} keeps running",
+                game.display_name
+            );
+            return;
+        };
+        let window = match self.windows.enumerate_windows() {
+            Ok(windows) => windows
+                .into_iter()
+                .find(|w| w.exe_name.eq_ignore_ascii_case(&game.exe_name)),
+            Err(e) => {
+                warn!("Failed to list windows: {:?}", e);
+                None
+            }
+        };
+        let Some(window) = window else {
+            warn!("{} has no window, not suspending it", game.display_name);
+            return;
+        };
+        if let Err(e) = suspender.suspend(window.pid, &window.exe_name) {
+            warn!("Failed to suspend {}: {:?}", game.display_name, e);
+        }
+    }
+
+    /// Resumes `game`'s process if it was suspended.
+    fn resume_game(&self, game: &GameConfig) {
+        if let Some(suspender) = &self.suspender {
+            if let Err(e) = suspender.resume_exe(&game.exe_name) {
+                warn!("Failed to resume {}: {:?}", game.display_name, e);
+            }
+        }
+    }
+
+    /// Resumes suspended processes whose game is no longer paused that way,
+    /// e.g. after it was disabled or switched to keys.
+    fn release_suspended(&self, config: &AppConfig) {
+        let Some(suspender) = &self.suspender else {
+            return;
+        };
+        for (pid, exe_name) in suspender.suspended() {
+            let still_suspended = config.games.iter().any(|g| {
+                g.enabled
+                    && g.pause_method == PauseMethod::Suspend
+                    && g.exe_name.eq_ignore_ascii_case(&exe_name)
+            });
+            if !still_suspended {
+                if let Err(e) = suspender.resume(pid) {
+                    warn!("Failed to resume {} (pid {}): {:?}", exe_name, pid, e);
+                }
+            }
+        }
+    }
+
+    /// Resumes every suspended game, for when nothing is played any more.
+    fn resume_all(&self) {
+        if let Some(suspender) = &self.suspender {
+            suspender.resume_all();
+        }
+    }
+
+    /// Sends a game's leave or enter keys the way it's set up to get them.
+    async fn send_keys(&self, game: &GameConfig, keys: &KeySequence, action: &str) {
+        if keys.is_empty() {
+            return;
+        }
+        info!("Sending {} to {}: {}", keys, action, game.display_name);
+
+        let window = match game.key_delivery {
+            KeyDelivery::Global => None,
+            KeyDelivery::Window if !self.input.can_post_keys() => {
+                warn!("Keys can't be sent to a window here, sending them globally");
+                None
+            }
+            KeyDelivery::Window => {
+                let window = self.windows.find_window_by_exe(&game.exe_name);
+                if window.is_none() {
+                    warn!("{} has no window, sending keys globally", game.display_name);
+                }
+                window
+            }
+        };
+
+        if let Some(window) = window {
+            let target = WindowInput {
+                input: &*self.input,
+                window,
+            };
+            match input::play(&target, &*self.clock, keys).await {
+                Ok(()) => return,
+                Err(e) => warn!(
+                    "Failed to send keys to the window, sending them globally: {:?}",
+                    e
+                ),
+            }
+        }
+        if let Err(e) = input::play(&*self.input, &*self.clock, keys).await {
+            warn!("Failed to send keys: {:?}", e);
+        }
+    }
+
+    /// Brings the game's window to the front and waits until it really is,
+    /// so enter keys never end up in OBS or a chat window instead.
+    async fn focus_game(&self, hwnd: isize, game: &GameConfig, config: &AppConfig) -> Result<()> {
+        let timeout = Duration::from_millis(config.focus_timeout_ms);
+        // Another window of the same process counts, e.g. a dialog the game
+        // opened; another instance or a launcher with the same exe doesn't
+        let pid = match self.windows.enumerate_windows() {
+            Ok(windows) => windows.iter().find(|w| w.hwnd == hwnd).map(|w| w.pid),
+            Err(e) => {
+                warn!("Failed to list windows: {:?}", e);
+                None
+            }
+        }
+        .filter(|&pid| pid != 0);
+        // A refused request is retried like a stolen focus, until the timeout
+        let mut focus_error = None;
+        let mut focus = || {
+            if let Err(e) = self.windows.focus_window(hwnd) {
+                warn!("Failed to focus {}: {:?}", game.display_name, e);
+                focus_error = Some(e);
+            }
+        };
+
+        focus();
+        let mut waited = Duration::from_millis(config.focus_settle_ms);
+        self.clock.sleep(waited).await;
+
+        loop {
+            let foreground = match self.windows.foreground_window() {
+                Ok(window) => window,
+                Err(e) => {
+                    warn!("Failed to read the foreground window: {:?}", e);
+                    None
+                }
+            };
+            match foreground {
+                Some(window) if window.hwnd == hwnd || pid == Some(window.pid) => return Ok(()),
+                _ if waited >= timeout => {
+                    let holder = match (foreground, focus_error) {
+                        (Some(w), _) => format!("{} has focus", w.exe_name),
+                        (None, Some(e)) => e.to_string(),
+                        (None, None) => "nothing has focus".to_string(),
+                    };
+                    anyhow::bail!(
+                        "{} didn't come to the front within {}ms ({})",
+                        game.display_name,
+                        timeout.as_millis(),
+                        holder
+                    );
+                }
+                _ => {}
+            }
+
+            // Focus may have been refused or taken back, so ask again
+            focus();
+            self.clock.sleep(FOCUS_RETRY_INTERVAL).await;
+            waited += FOCUS_RETRY_INTERVAL;
+        }
+    }
+
     fn find_next_game<'a>(
         &self,
+        config: &AppConfig,
         games: &[&'a GameConfig],
-        current_exe: Option<&str>,
+        state: &SwapState,
     ) -> Result<&'a GameConfig> {
-        let available: Vec<&&GameConfig> = games
+        if games.is_empty() {
+            anyhow::bail!("No games configured");
+        }
+        let current_exe = state.current_exe.as_deref();
+        Ok(self
+            .pick_next(config, games, &[current_exe], &state.total_times)
+            .unwrap_or(games[0]))
+    }
+
+    /// Picks a game with an open window, other than the `excluded` ones, with
+    /// the configured strategy. The first excluded game counts as the one on
+    /// screen.
+    fn pick_next<'a>(
+        &self,
+        config: &AppConfig,
+        games: &[&'a GameConfig],
+        excluded: &[Option<&str>],
+        total_times: &HashMap<String, u64>,
+    ) -> Option<&'a GameConfig> {
+        let available: Vec<&GameConfig> = games
             .iter()
-            .filter(|g| {
-                if let Some(current) = current_exe {
-                    g.exe_name != current
-                } else {
-                    true
-                }
-            })
-            .filter(|g| find_window_by_exe(&g.exe_name).is_some())
+            .filter(|g| !excluded.contains(&Some(g.exe_name.as_str())))
+            .filter(|g| self.windows.find_window_by_exe(&g.exe_name).is_some())
+            .copied()
             .collect();
-
         if available.is_empty() {
-            if games.is_empty() {
-                anyhow::bail!("No games configured");
+            return None;
+        }
+
+        let ctx = SelectionContext {
+            games,
+            candidates: &available,
+            current_exe: excluded.first().copied().flatten(),
+            total_times,
+        };
+        let picked = self.selector.lock().unwrap().pick(
+            config.selection_strategy,
+            &ctx,
+            self.rng.lock().unwrap().picks(),
+        );
+        Some(picked.unwrap_or(available[0]))
+    }
+
+    /// The first scheduled swap that can happen now, skipping games that left
+    /// the rotation, are on screen, or have no window.
+    fn take_scheduled<'a>(
+        &self,
+        in_rotation: &[&'a GameConfig],
+        state: &SwapState,
+    ) -> Option<(&'a GameConfig, ScheduledSwap)> {
+        state.schedule.iter().find_map(|entry| {
+            let game = find_game(in_rotation, &entry.exe_name).ok()?;
+            let on_screen = state
+                .current_exe
+                .as_deref()
+                .is_some_and(|exe| exe.eq_ignore_ascii_case(&game.exe_name));
+            let has_window = self.windows.find_window_by_exe(&game.exe_name).is_some();
+            (!on_screen && has_window).then(|| (game, entry.clone()))
+        })
+    }
+
+    /// Plans a swap to follow `previous`, avoiding `next` too when possible.
+    fn plan_swap(
+        &self,
+        config: &AppConfig,
+        in_rotation: &[&GameConfig],
+        previous: Option<&str>,
+        next: Option<&str>,
+        total_times: &HashMap<String, u64>,
+        id: u64,
+    ) -> Option<ScheduledSwap> {
+        let game = self
+            .pick_next(config, in_rotation, &[previous, next], total_times)
+            .or_else(|| self.pick_next(config, in_rotation, &[previous], total_times))?;
+        let duration_seconds =
+            calculate_delay(config, Some(game), self.rng.lock().unwrap().delays());
+        Some(ScheduledSwap {
+            id,
+            game_name: game.display_name.clone(),
+            exe_name: game.exe_name.clone(),
+            duration_seconds,
+        })
+    }
+
+    /// Tops the schedule up to `schedule_ahead` swaps, after dropping the ones
+    /// for games that left the rotation. Clears it when planning is off.
+    async fn fill_schedule(&self) {
+        let config = self.config.read().await;
+        let state = self.app_state.get_state().await;
+        let wanted = config.schedule_ahead as usize;
+        if wanted == 0 || state.session_ended_at.is_some() {
+            if !state.schedule.is_empty() {
+                self.app_state.update_state(|s| s.schedule.clear()).await;
+            }
+            return;
+        }
+
+        if let Some(script) = &self.script {
+            let schedule: Vec<ScheduledSwap> = (state.script_position..)
+                .map_while(|position| Some((position, script.entry(position)?)))
+                .take(wanted)
+                .map(|(position, entry)| ScheduledSwap {
+                    id: position,
+                    game_name: entry.game_name.clone(),
+                    exe_name: entry.exe_name.clone(),
+                    duration_seconds: entry.duration.as_secs(),
+                })
+                .collect();
+            if schedule != state.schedule {
+                self.app_state.update_state(|s| s.schedule = schedule).await;
             }
-            return Ok(games[0]);
+            return;
         }
 
-        use rand::Rng;
-        let idx = rand::thread_rng().gen_range(0..available.len());
-        Ok(available[idx])
+        let now = self.clock.now();
+        let in_rotation: Vec<&GameConfig> = config
+            .games
+            .iter()
+            .filter(|g| g.enabled && check_in_rotation(&state, g, now).is_ok())
+            .collect();
+
+        let mut schedule = state.schedule.clone();
+        let mut previous = state.current_exe.clone();
+        schedule.retain(|entry| {
+            let keep = find_game(&in_rotation, &entry.exe_name).is_ok()
+                && previous.as_deref() != Some(entry.exe_name.as_str());
+            if keep {
+                previous = Some(entry.exe_name.clone());
+            }
+            keep
+        });
+        schedule.truncate(wanted);
+        if schedule == state.schedule && schedule.len() == wanted {
+            return;
+        }
+
+        // Least played first should see the time the planned swaps will add
+        let mut total_times = state.total_times.clone();
+        for entry in &schedule {
+            *total_times.entry(entry.game_name.clone()).or_default() += entry.duration_seconds;
+        }
+        let mut seq = state.schedule_seq;
+        while schedule.len() < wanted {
+            let Some(entry) = self.plan_swap(
+                &config,
+                &in_rotation,
+                previous.as_deref(),
+                None,
+                &total_times,
+                seq,
+            ) else {
+                break;
+            };
+            seq += 1;
+            *total_times.entry(entry.game_name.clone()).or_default() += entry.duration_seconds;
+            previous = Some(entry.exe_name.clone());
+            schedule.push(entry);
+        }
+
+        let (position, bag) = self.draws();
+        self.app_state
+            .update_state(|s| {
+                s.schedule = schedule;
+                s.schedule_seq = seq;
+                s.rng_position = position;
+                s.shuffle_bag = bag;
+            })
+            .await;
     }
 
-    pub async fn force_swap(&self) -> Result<()> {
-        self.do_swap().await
+    async fn move_scheduled_swap(&self, id: u64, to: usize) -> Result<()> {
+        if self.script.is_some() {
+            anyhow::bail!("The schedule comes from the script");
+        }
+        let state = self.app_state.get_state().await;
+        let mut schedule = state.schedule.clone();
+        let from = schedule
+            .iter()
+            .position(|e| e.id == id)
+            .ok_or_else(|| anyhow::anyhow!("No scheduled swap with id {}", id))?;
+        let entry = schedule.remove(from);
+        schedule.insert(to.min(schedule.len()), entry);
+
+        let mut previous = state.current_exe.as_deref();
+        for entry in &schedule {
+            if previous == Some(entry.exe_name.as_str()) {
+                anyhow::bail!("{} would be swapped in twice in a row", entry.game_name);
+            }
+            previous = Some(&entry.exe_name);
+        }
+
+        self.app_state.update_state(|s| s.schedule = schedule).await;
+        Ok(())
+    }
+
+    async fn reroll_scheduled_swap(&self, id: Option<u64>) -> Result<()> {
+        if self.script.is_some() {
+            anyhow::bail!("The schedule comes from the script");
+        }
+        let Some(id) = id else {
+            info!("Rerolling the whole schedule");
+            self.app_state.update_state(|s| s.schedule.clear()).await;
+            return Ok(());
+        };
+
+        let config = self.config.read().await;
+        let state = self.app_state.get_state().await;
+        let index = state
+            .schedule
+            .iter()
+            .position(|e| e.id == id)
+            .ok_or_else(|| anyhow::anyhow!("No scheduled swap with id {}", id))?;
+
+        let now = self.clock.now();
+        let in_rotation: Vec<&GameConfig> = config
+            .games
+            .iter()
+            .filter(|g| g.enabled && check_in_rotation(&state, g, now).is_ok())
+            .collect();
+        let previous = match index {
+            0 => state.current_exe.as_deref(),
+            i => Some(state.schedule[i - 1].exe_name.as_str()),
+        };
+        let next = state.schedule.get(index + 1).map(|e| e.exe_name.as_str());
+        let entry = self
+            .plan_swap(
+                &config,
+                &in_rotation,
+                previous,
+                next,
+                &state.total_times,
+                id,
+            )
+            .ok_or_else(|| anyhow::anyhow!("No other game to swap in"))?;
+
+        info!("Rerolled scheduled swap {} to {}", id, entry.game_name);
+        let (position, bag) = self.draws();
+        self.app_state
+            .update_state(|s| {
+                if let Some(slot) = s.schedule.iter_mut().find(|e| e.id == id) {
+                    *slot = entry;
+                }
+                s.rng_position = position;
+                s.shuffle_bag = bag;
+            })
+            .await;
+        Ok(())
     }
 }
@@ -0,0 +1,179 @@
+use super::{WindowBackend, WindowInfo};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// A fake window entry, as read from a `--simulate` file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MockWindowConfig {
+    pub exe_name: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub focus_fails: bool,
+    /// Focusing reports success, but the window never comes to the front.
+    #[serde(default)]
+    pub focus_ignored: bool,
+    /// Made up from the window handle when not given.
+    #[serde(default)]
+    pub pid: Option<u32>,
+}
+
+/// Current state of a fake window, as reported by the simulation API.
+#[derive(Debug, Clone, Serialize)]
+pub struct MockWindowStatus {
+    pub hwnd: isize,
+    pub title: String,
+    pub exe_name: String,
+    pub pid: u32,
+    pub focus_fails: bool,
+    pub focus_ignored: bool,
+    pub focused: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SimulationFile {
+    #[serde(default)]
+    windows: Vec<MockWindowConfig>,
+}
+
+struct MockWindow {
+    info: WindowInfo,
+    focus_fails: bool,
+    focus_ignored: bool,
+}
+
+#[derive(Default)]
+struct MockState {
+    windows: Vec<MockWindow>,
+    focused: Option<isize>,
+    next_hwnd: isize,
+}
+
+/// In-memory window backend with scriptable windows and focus behaviour.
+#[derive(Default)]
+pub struct MockWindowBackend {
+    state: Mutex<MockState>,
+}
+
+impl MockWindowBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read simulation file: {:?}", path))?;
+        let file: SimulationFile =
+            toml::from_str(&content).with_context(|| "Failed to parse simulation file")?;
+
+        let backend = Self::new();
+        for window in file.windows {
+            backend.add(window);
+        }
+        Ok(backend)
+    }
+
+    pub fn add(&self, window: MockWindowConfig) -> isize {
+        let mut state = self.state.lock().unwrap();
+        state.next_hwnd += 1;
+        let hwnd = state.next_hwnd;
+
+        let title = window.title.unwrap_or_else(|| window.exe_name.clone());
+        state.windows.push(MockWindow {
+            info: WindowInfo {
+                hwnd,
+                title,
+                exe_name: window.exe_name,
+                pid: window.pid.unwrap_or(10_000 + hwnd as u32),
+            },
+            focus_fails: window.focus_fails,
+            focus_ignored: window.focus_ignored,
+        });
+        hwnd
+    }
+
+    pub fn remove(&self, exe_name: &str) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let before = state.windows.len();
+        state
+            .windows
+            .retain(|w| !w.info.exe_name.eq_ignore_ascii_case(exe_name));
+        state.windows.len() != before
+    }
+
+    pub fn set_focus_fails(&self, exe_name: &str, fails: bool) -> bool {
+        self.update(exe_name, |w| w.focus_fails = fails)
+    }
+
+    pub fn set_focus_ignored(&self, exe_name: &str, ignored: bool) -> bool {
+        self.update(exe_name, |w| w.focus_ignored = ignored)
+    }
+
+    fn update(&self, exe_name: &str, f: impl Fn(&mut MockWindow)) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let mut found = false;
+        for window in state
+            .windows
+            .iter_mut()
+            .filter(|w| w.info.exe_name.eq_ignore_ascii_case(exe_name))
+        {
+            f(window);
+            found = true;
+        }
+        found
+    }
+
+    pub fn snapshot(&self) -> Vec<MockWindowStatus> {
+        let state = self.state.lock().unwrap();
+        state
+            .windows
+            .iter()
+            .map(|w| MockWindowStatus {
+                hwnd: w.info.hwnd,
+                title: w.info.title.clone(),
+                exe_name: w.info.exe_name.clone(),
+                pid: w.info.pid,
+                focus_fails: w.focus_fails,
+                focus_ignored: w.focus_ignored,
+                focused: state.focused == Some(w.info.hwnd),
+            })
+            .collect()
+    }
+}
+
+impl WindowBackend for MockWindowBackend {
+    fn enumerate_windows(&self) -> Result<Vec<WindowInfo>> {
+        let state = self.state.lock().unwrap();
+        Ok(state.windows.iter().map(|w| w.info.clone()).collect())
+    }
+
+    fn focus_window(&self, hwnd: isize) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let Some(window) = state.windows.iter().find(|w| w.info.hwnd == hwnd) else {
+            anyhow::bail!("No such window: {}", hwnd);
+        };
+
+        if window.focus_fails {
+            anyhow::bail!("Failed to focus window: {}", window.info.exe_name);
+        }
+
+        if !window.focus_ignored {
+            state.focused = Some(hwnd);
+        }
+        Ok(())
+    }
+
+    fn foreground_window(&self) -> Result<Option<WindowInfo>> {
+        let state = self.state.lock().unwrap();
+        Ok(state.focused.and_then(|hwnd| {
+            state
+                .windows
+                .iter()
+                .find(|w| w.info.hwnd == hwnd)
+                .map(|w| w.info.clone())
+        }))
+    }
+}
@@ -0,0 +1,60 @@
+mod mock;
+#[cfg(windows)]
+mod win32;
+#[cfg(target_os = "linux")]
+mod x11;
+
+use crate::input::InputBackend;
+use anyhow::Result;
+use std::sync::Arc;
+
+pub use mock::{MockWindowBackend, MockWindowConfig};
+
+#[derive(Debug, Clone)]
+pub struct WindowInfo {
+    pub hwnd: isize,
+    pub title: String,
+    pub exe_name: String,
+    /// Id of the process owning the window, 0 if unknown.
+    pub pid: u32,
+}
+
+/// Platform window management used by the swapper and the `/api/windows` endpoint.
+pub trait WindowBackend: Send + Sync {
+    fn enumerate_windows(&self) -> Result<Vec<WindowInfo>>;
+
+    /// Asks for `hwnd` to be brought to the front. The window may come up
+    /// later or not at all, so check with `foreground_window`.
+    fn focus_window(&self, hwnd: isize) -> Result<()>;
+
+    /// The window that currently has keyboard focus, if any.
+    fn foreground_window(&self) -> Result<Option<WindowInfo>>;
+
+    fn find_window_by_exe(&self, exe_name: &str) -> Option<isize> {
+        let windows = self.enumerate_windows().ok()?;
+        windows
+            .iter()
+            .find(|w| w.exe_name.eq_ignore_ascii_case(exe_name))
+            .map(|w| w.hwnd)
+    }
+}
+
+/// Picks the window backend for the platform we were built for. Focusing a
+/// window may need a keypress, which goes through `input`.
+#[cfg_attr(not(windows), allow(unused_variables))]
+pub fn default_backend(input: Arc<dyn InputBackend>) -> Result<Arc<dyn WindowBackend>> {
+    #[cfg(windows)]
+    {
+        Ok(Arc::new(win32::Win32Backend::new(input)))
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        Ok(Arc::new(x11::X11Backend::connect()?))
+    }
+
+    #[cfg(not(any(windows, target_os = "linux")))]
+    {
+        anyhow::bail!("No window backend is available for this platform")
+    }
+}
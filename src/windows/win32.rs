@@ -1,25 +1,58 @@
+use super::{WindowBackend, WindowInfo};
+use crate::input::InputBackend;
+use crate::keys::Key;
 use anyhow::{Context, Result};
 use std::ffi::OsString;
 use std::os::windows::ffi::OsStringExt;
+use std::sync::Arc;
 use windows::Win32::Foundation::{BOOL, HWND, LPARAM};
 use windows::Win32::System::ProcessStatus::GetModuleFileNameExW;
 use windows::Win32::System::Threading::{
     AttachThreadInput, GetCurrentThreadId, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION,
 };
-use windows::Win32::UI::Input::KeyboardAndMouse::{keybd_event, SetFocus, KEYEVENTF_KEYUP};
+use windows::Win32::UI::Input::KeyboardAndMouse::SetFocus;
 use windows::Win32::UI::WindowsAndMessaging::{
     BringWindowToTop, EnumWindows, GetForegroundWindow, GetWindowTextLengthW, GetWindowTextW,
     GetWindowThreadProcessId, IsWindowVisible, SetForegroundWindow, ShowWindow, SW_RESTORE,
 };
 
-#[derive(Debug, Clone)]
-pub struct WindowInfo {
-    pub hwnd: isize,
-    pub title: String,
-    pub exe_name: String,
+pub struct Win32Backend {
+    input: Arc<dyn InputBackend>,
 }
 
-pub fn enumerate_windows() -> Result<Vec<WindowInfo>> {
+impl Win32Backend {
+    pub fn new(input: Arc<dyn InputBackend>) -> Self {
+        Self { input }
+    }
+}
+
+impl WindowBackend for Win32Backend {
+    fn enumerate_windows(&self) -> Result<Vec<WindowInfo>> {
+        enumerate_windows()
+    }
+
+    fn focus_window(&self, hwnd: isize) -> Result<()> {
+        focus_window(&*self.input, hwnd)
+    }
+
+    fn foreground_window(&self) -> Result<Option<WindowInfo>> {
+        unsafe {
+            let hwnd = GetForegroundWindow();
+            if hwnd.0 == 0 {
+                return Ok(None);
+            }
+
+            Ok(Some(WindowInfo {
+                hwnd: hwnd.0,
+                title: get_window_title(hwnd),
+                exe_name: get_window_exe(hwnd),
+                pid: get_window_pid(hwnd),
+            }))
+        }
+    }
+}
+
+fn enumerate_windows() -> Result<Vec<WindowInfo>> {
     let mut windows: Vec<WindowInfo> = Vec::new();
 
     unsafe {
@@ -54,6 +87,7 @@ unsafe extern "system" fn enum_windows_callback(hwnd: HWND, lparam: LPARAM) -> B
         hwnd: hwnd.0,
         title,
         exe_name,
+        pid: get_window_pid(hwnd),
     });
 
     BOOL(1)
@@ -76,9 +110,14 @@ unsafe fn get_window_title(hwnd: HWND) -> String {
     }
 }
 
-unsafe fn get_window_exe(hwnd: HWND) -> String {
+unsafe fn get_window_pid(hwnd: HWND) -> u32 {
     let mut process_id: u32 = 0;
     GetWindowThreadProcessId(hwnd, Some(&mut process_id));
+    process_id
+}
+
+unsafe fn get_window_exe(hwnd: HWND) -> String {
+    let process_id = get_window_pid(hwnd);
 
     if process_id == 0 {
         return String::new();
@@ -107,42 +146,13 @@ unsafe fn get_window_exe(hwnd: HWND) -> String {
         .unwrap_or_default()
 }
 
-pub fn find_window_by_exe(exe_name: &str) -> Option<isize> {
-    let windows = enumerate_windows().ok()?;
-    windows
-        .iter()
-        .find(|w| w.exe_name.eq_ignore_ascii_case(exe_name))
-        .map(|w| w.hwnd)
-}
-
-pub fn focus_window(hwnd: isize) -> Result<()> {
-    const MAX_RETRIES: u32 = 5;
-
-    for attempt in 0..MAX_RETRIES {
-        if focus_window_once(hwnd)? {
-            return Ok(());
-        }
-
-        if attempt < MAX_RETRIES - 1 {
-            std::thread::sleep(std::time::Duration::from_millis(50));
-        }
-    }
-
-    anyhow::bail!(
-        "Failed to set foreground window after {} attempts",
-        MAX_RETRIES
-    )
-}
+fn focus_window(input: &dyn InputBackend, hwnd: isize) -> Result<()> {
+    // Simulate an ALT keypress to bypass Windows foreground lock
+    input.tap(Key::Alt)?;
 
-fn focus_window_once(hwnd: isize) -> Result<bool> {
     unsafe {
         let hwnd = HWND(hwnd);
 
-        // Simulate an ALT keypress to bypass Windows foreground lock
-        // Virtual-Key code for ALT is VK_MENU (0x12)
-        keybd_event(0x12, 0, Default::default(), 0);
-        keybd_event(0x12, 0, KEYEVENTF_KEYUP, 0);
-
         ShowWindow(hwnd, SW_RESTORE);
 
         let foreground_hwnd = GetForegroundWindow();
@@ -166,8 +176,7 @@ fn focus_window_once(hwnd: isize) -> Result<bool> {
         if attached {
             AttachThreadInput(current_thread_id, foreground_thread_id, false);
         }
-
-        let new_foreground = GetForegroundWindow();
-        Ok(new_foreground == hwnd)
     }
+
+    Ok(())
 }
@@ -0,0 +1,220 @@
+use super::{WindowBackend, WindowInfo};
+use anyhow::{Context, Result};
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{AtomEnum, ClientMessageEvent, ConnectionExt, EventMask, Window};
+use x11rb::rust_connection::RustConnection;
+use x11rb::CURRENT_TIME;
+
+x11rb::atom_manager! {
+    Atoms: AtomsCookie {
+        _NET_CLIENT_LIST,
+        _NET_ACTIVE_WINDOW,
+        _NET_WM_PID,
+        _NET_WM_NAME,
+        UTF8_STRING,
+    }
+}
+
+// Source indication for _NET_ACTIVE_WINDOW; "pager" requests are honoured
+// by window managers that ignore focus stealing from normal applications.
+const SOURCE_PAGER: u32 = 2;
+
+pub struct X11Backend {
+    conn: RustConnection,
+    root: Window,
+    atoms: Atoms,
+}
+
+impl X11Backend {
+    pub fn connect() -> Result<Self> {
+        let (conn, screen_num) =
+            x11rb::connect(None).context("Failed to connect to the X server")?;
+        let root = conn.setup().roots[screen_num].root;
+        let atoms = Atoms::new(&conn)?
+            .reply()
+            .context("Failed to intern EWMH atoms")?;
+
+        Ok(Self { conn, root, atoms })
+    }
+
+    fn client_list(&self) -> Result<Vec<Window>> {
+        let reply = self
+            .conn
+            .get_property(
+                false,
+                self.root,
+                self.atoms._NET_CLIENT_LIST,
+                AtomEnum::WINDOW,
+                0,
+                u32::MAX,
+            )?
+            .reply()
+            .context("Failed to read _NET_CLIENT_LIST")?;
+
+        Ok(reply.value32().map(|v| v.collect()).unwrap_or_default())
+    }
+
+    fn active_window(&self) -> Result<Option<Window>> {
+        let reply = self
+            .conn
+            .get_property(
+                false,
+                self.root,
+                self.atoms._NET_ACTIVE_WINDOW,
+                AtomEnum::WINDOW,
+                0,
+                1,
+            )?
+            .reply()?;
+
+        Ok(reply
+            .value32()
+            .and_then(|mut v| v.next())
+            .filter(|&w| w != 0))
+    }
+
+    fn window_title(&self, window: Window) -> String {
+        let utf8 = self
+            .conn
+            .get_property(
+                false,
+                window,
+                self.atoms._NET_WM_NAME,
+                self.atoms.UTF8_STRING,
+                0,
+                u32::MAX,
+            )
+            .ok()
+            .and_then(|c| c.reply().ok())
+            .filter(|r| !r.value.is_empty());
+
+        let reply = utf8.or_else(|| {
+            self.conn
+                .get_property(
+                    false,
+                    window,
+                    AtomEnum::WM_NAME,
+                    AtomEnum::STRING,
+                    0,
+                    u32::MAX,
+                )
+                .ok()
+                .and_then(|c| c.reply().ok())
+        });
+
+        reply
+            .map(|r| String::from_utf8_lossy(&r.value).into_owned())
+            .unwrap_or_default()
+    }
+
+    fn window_pid(&self, window: Window) -> Option<u32> {
+        self.conn
+            .get_property(
+                false,
+                window,
+                self.atoms._NET_WM_PID,
+                AtomEnum::CARDINAL,
+                0,
+                1,
+            )
+            .ok()?
+            .reply()
+            .ok()?
+            .value32()?
+            .next()
+    }
+
+    fn request_focus(&self, window: Window) -> Result<()> {
+        let event = ClientMessageEvent::new(
+            32,
+            window,
+            self.atoms._NET_ACTIVE_WINDOW,
+            [SOURCE_PAGER, CURRENT_TIME, 0, 0, 0],
+        );
+
+        self.conn.map_window(window)?;
+        self.conn.send_event(
+            false,
+            self.root,
+            EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+            event,
+        )?;
+        self.conn.flush()?;
+        Ok(())
+    }
+}
+
+impl WindowBackend for X11Backend {
+    fn enumerate_windows(&self) -> Result<Vec<WindowInfo>> {
+        let mut windows = Vec::new();
+
+        for window in self.client_list()? {
+            let title = self.window_title(window);
+            if title.is_empty() {
+                continue;
+            }
+
+            let Some(pid) = self.window_pid(window) else {
+                continue;
+            };
+            let Some(exe_name) = process_exe_name(pid) else {
+                continue;
+            };
+
+            windows.push(WindowInfo {
+                hwnd: window as isize,
+                title,
+                exe_name,
+                pid,
+            });
+        }
+
+        Ok(windows)
+    }
+
+    fn focus_window(&self, hwnd: isize) -> Result<()> {
+        // The window manager applies the request asynchronously
+        self.request_focus(hwnd as Window)
+    }
+
+    fn foreground_window(&self) -> Result<Option<WindowInfo>> {
+        let Some(window) = self.active_window()? else {
+            return Ok(None);
+        };
+
+        let pid = self.window_pid(window);
+        Ok(Some(WindowInfo {
+            hwnd: window as isize,
+            title: self.window_title(window),
+            exe_name: pid.and_then(process_exe_name).unwrap_or_default(),
+            pid: pid.unwrap_or(0),
+        }))
+    }
+}
+
+fn process_exe_name(pid: u32) -> Option<String> {
+    let exe_path = std::fs::read_link(format!("/proc/{}/exe", pid)).ok()?;
+    let exe_name = exe_path.file_name()?.to_string_lossy().into_owned();
+
+    // Wine/Proton games all run under the wine loader, so use the
+    // Windows executable from argv[0] to keep exe names the same as on Windows.
+    if exe_name.starts_with("wine") {
+        if let Some(name) = process_argv0_name(pid) {
+            return Some(name);
+        }
+    }
+
+    Some(exe_name)
+}
+
+fn process_argv0_name(pid: u32) -> Option<String> {
+    let cmdline = std::fs::read(format!("/proc/{}/cmdline", pid)).ok()?;
+    let argv0 = cmdline.split(|&b| b == 0).next()?;
+    let argv0 = String::from_utf8_lossy(argv0);
+
+    argv0
+        .rsplit(['/', '\\'])
+        .next()
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+}
@@ -2,13 +2,20 @@ let ws = null;
 let config = null;
 let state = null;
 let windows = [];
+let failedAtSwap = null;
 
 function connectWS() {
   const protocol = window.location.protocol === "https:" ? "wss:" : "ws:";
   ws = new WebSocket(`${protocol}//${window.location.host}/ws`);
 
   ws.onmessage = (event) => {
-    state = JSON.parse(event.data);
+    const data = JSON.parse(event.data);
+    // Other session events (e.g. game_completed) are for overlays; state follows separately
+    if (data.event) {
+      if (data.event === "swap_failed") showSwapFailure(data);
+      return;
+    }
+    state = data;
     updateStateDisplay();
   };
 
@@ -36,17 +43,36 @@ async function fetchWindows() {
   renderWindowPicker();
 }
 
+function showSwapFailure(failure) {
+  const at = new Date(failure.failed_at).toLocaleTimeString();
+  document.getElementById("swap-failure").textContent =
+    `${at}: couldn't swap to ${failure.game_name}. ${failure.reason}`;
+  document.getElementById("swap-failure-panel").style.display = "";
+  failedAtSwap = state ? state.swap_count : null;
+}
+
 function updateStateDisplay() {
   if (!state) return;
 
+  // The failure notice stays up until a swap goes through
+  if (failedAtSwap !== null && state.swap_count !== failedAtSwap) {
+    document.getElementById("swap-failure-panel").style.display = "none";
+    failedAtSwap = null;
+  }
+
   const statusBadge = document.getElementById("status-badge");
   const statusText = document.getElementById("status-text");
   const currentGame = document.getElementById("current-game");
   const timeSince = document.getElementById("time-since");
   const nextSwap = document.getElementById("next-swap");
   const swapCount = document.getElementById("swap-count");
+  const upNext = document.getElementById("up-next");
+  document.getElementById("seed").textContent = state.seed ?? "-";
 
-  if (state.is_paused) {
+  if (state.session_ended_at) {
+    statusText.textContent = "Finished";
+    statusBadge.className = "status-badge waiting";
+  } else if (state.is_paused) {
     statusText.textContent = "Paused";
     statusBadge.className = "status-badge paused";
   } else if (state.current_game) {
@@ -82,31 +108,226 @@ function updateStateDisplay() {
     nextSwap.textContent = "--:--";
   }
 
+  if (
+    config &&
+    config.hide_next_swap &&
+    (state.queued_game || (state.schedule || []).length)
+  ) {
+    upNext.textContent = "REDACTED";
+  } else {
+    const scheduled = (state.schedule || [])[0];
+    upNext.textContent =
+      state.queued_game || (scheduled && scheduled.game_name) || "Random";
+  }
+
   swapCount.textContent = state.swap_count || 0;
 
   renderHistory();
+  renderSchedule();
+  renderBudgets();
+  renderCompleted();
+  renderSessions();
 }
 
-function renderHistory() {
-  const container = document.getElementById("swap-history");
-  if (!state || !state.history || state.history.length === 0) {
-    container.innerHTML =
-      '<div class="empty-state" style="padding: 1.5rem;">No history yet</div>';
+function renderSchedule() {
+  const panel = document.getElementById("schedule-panel");
+  const container = document.getElementById("schedule-list");
+  const schedule = state.schedule || [];
+  if (schedule.length === 0) {
+    panel.style.display = "none";
+    return;
+  }
+
+  panel.style.display = "";
+  // Planned games stay hidden from the operator like the rest of the next swap info
+  const hidden = config && config.hide_next_swap;
+  let startsIn = Math.max(0, state.time_until_swap_seconds || 0);
+  container.innerHTML = schedule
+    .map((item, index) => {
+      const row = `
+        <div style="display: flex; justify-content: space-between; align-items: center; gap: 0.5rem; padding: 0.75rem 1.25rem; border-bottom: 1px solid var(--border);">
+            <div style="font-weight: 500; font-size: 0.875rem;">${index + 1}. ${hidden ? "REDACTED" : escapeHtml(item.game_name)}</div>
+            <div style="font-family: var(--mono); font-size: 0.8125rem; color: var(--muted);">${hidden ? "" : `in ${formatTime(startsIn)} for ${formatTime(item.duration_seconds)}`}</div>
+            <div style="display: flex; gap: 0.25rem;">
+                <button class="btn btn-small" onclick="moveScheduled(${item.id}, ${index - 1})" ${index === 0 ? "disabled" : ""}>Up</button>
+                <button class="btn btn-small" onclick="moveScheduled(${item.id}, ${index + 1})" ${index === schedule.length - 1 ? "disabled" : ""}>Down</button>
+                <button class="btn btn-small" onclick="rerollScheduled(${item.id})">Reroll</button>
+            </div>
+        </div>
+    `;
+      startsIn += item.duration_seconds;
+      return row;
+    })
+    .join("");
+}
+
+function renderCompleted() {
+  const panel = document.getElementById("completed-panel");
+  const container = document.getElementById("completed-list");
+  const completed = state.completed || [];
+  if (completed.length === 0) {
+    panel.style.display = "none";
     return;
   }
 
-  container.innerHTML = state.history
+  panel.style.display = "";
+  container.innerHTML = completed
     .map(
       (item) => `
         <div style="display: flex; justify-content: space-between; align-items: center; padding: 0.75rem 1.25rem; border-bottom: 1px solid var(--border);">
             <div style="font-weight: 500; font-size: 0.875rem;">${escapeHtml(item.game_name)}</div>
-            <div style="font-family: var(--mono); font-size: 0.8125rem; color: var(--muted);">${formatTime(item.duration_seconds)}</div>
+            <div style="font-family: var(--mono); font-size: 0.8125rem; color: var(--muted);">${formatTime(item.total_seconds)} &middot; ${new Date(item.completed_at).toLocaleTimeString()}</div>
         </div>
     `,
     )
     .join("");
 }
 
+function renderBudgets() {
+  const panel = document.getElementById("budget-panel");
+  const container = document.getElementById("budget-list");
+  const budgets = Object.entries(state.time_budgets || {});
+  if (budgets.length === 0) {
+    panel.style.display = "none";
+    return;
+  }
+
+  panel.style.display = "";
+  container.innerHTML = budgets
+    .map(([exe, budget]) => {
+      const name = gameName(exe);
+      const remaining = Math.max(0, budget - playedSeconds(exe));
+      const color = remaining === 0 ? "var(--muted)" : "var(--fg)";
+      return `
+        <div style="display: flex; justify-content: space-between; align-items: center; padding: 0.75rem 1.25rem; border-bottom: 1px solid var(--border);">
+            <div style="font-weight: 500; font-size: 0.875rem; color: ${color};">${escapeHtml(name)}</div>
+            <div style="font-family: var(--mono); font-size: 0.8125rem; color: var(--muted);">${remaining === 0 ? "Done" : `${formatTime(remaining)} left`}</div>
+        </div>
+    `;
+    })
+    .join("");
+}
+
+// Seconds played in a game, by exe name, including the stint in progress
+function playedSeconds(exe) {
+  let played = (state.played_times || {})[exe] || 0;
+  if (exe === state.current_exe && state.time_since_swap_seconds) {
+    played += Math.max(0, state.time_since_swap_seconds);
+  }
+  return played;
+}
+
+function gameName(exe) {
+  const game = ((config && config.games) || []).find((g) => g.exe_name === exe);
+  return game ? game.display_name : exe;
+}
+
+const HISTORY_PAGE = 25;
+let history = [];
+let historyTotal = 0;
+let historyLimit = HISTORY_PAGE;
+let historyLen = null;
+
+// The state only carries the latest stints, so the full list comes from /api/history
+async function renderHistory() {
+  if (!state || state.history_len === historyLen) return;
+  historyLen = state.history_len;
+  await fetchHistory();
+}
+
+async function fetchHistory() {
+  const res = await fetch(`/api/history?limit=${historyLimit}`);
+  const page = await res.json();
+  history = page.items;
+  historyTotal = page.total;
+  drawHistory();
+}
+
+async function showMoreHistory() {
+  historyLimit += HISTORY_PAGE;
+  await fetchHistory();
+}
+
+function drawHistory() {
+  const container = document.getElementById("swap-history");
+  if (history.length === 0) {
+    container.innerHTML =
+      '<div class="empty-state" style="padding: 1.5rem;">No history yet</div>';
+    return;
+  }
+
+  const rows = history
+    .map((item) => {
+      const started = new Date(item.started_at).toLocaleTimeString();
+      const ended = new Date(item.ended_at).toLocaleTimeString();
+      const details = [
+        `${started} - ${ended}`,
+        item.trigger !== "automatic" ? item.trigger : null,
+        item.paused_seconds > 0 ? `paused ${formatTime(item.paused_seconds)}` : null,
+      ]
+        .filter(Boolean)
+        .join(" &middot; ");
+      return `
+        <div style="display: flex; justify-content: space-between; align-items: center; padding: 0.75rem 1.25rem; border-bottom: 1px solid var(--border);">
+            <div>
+                <div style="font-weight: 500; font-size: 0.875rem;">${escapeHtml(item.game_name)}</div>
+                <div style="font-size: 0.75rem; color: var(--muted);">${details}</div>
+            </div>
+            <div style="font-family: var(--mono); font-size: 0.8125rem; color: var(--muted);">${formatTime(item.duration_seconds)}</div>
+        </div>
+    `;
+    })
+    .join("");
+
+  const more =
+    historyTotal > history.length
+      ? `<button class="btn btn-small" style="margin: 0.75rem 1.25rem;" onclick="showMoreHistory()">Show more (${historyTotal - history.length} older)</button>`
+      : "";
+  container.innerHTML = rows + more;
+}
+
+async function fetchStats() {
+  const res = await fetch("/api/stats");
+  drawStats(await res.json());
+}
+
+function drawStats(stats) {
+  const table = document.getElementById("stats-table");
+  const fairness = document.getElementById("stats-fairness");
+  if (stats.games.length === 0) {
+    table.innerHTML = "";
+    fairness.textContent = "";
+    return;
+  }
+
+  const span = (seconds) => (seconds == null ? "-" : formatTime(seconds));
+  const rows = stats.games
+    .map(
+      (game) => `
+        <tr>
+            <td>${escapeHtml(game.game_name)}</td>
+            <td>${game.swap_ins}</td>
+            <td>${game.swap_ins ? formatTime(Math.round(game.average_stint_seconds)) : "-"}</td>
+            <td>${span(game.shortest_stint_seconds)}</td>
+            <td>${span(game.longest_stint_seconds)}</td>
+            <td>${(game.share * 100).toFixed(1)}%</td>
+            <td>${span(game.since_last_played_seconds)}</td>
+            <td>${span(game.longest_gap_seconds)}</td>
+            <td>${game.random_picks} / ${game.expected_picks.toFixed(1)}</td>
+        </tr>`,
+    )
+    .join("");
+  table.innerHTML = `
+    <tr><th>Game</th><th>Swaps</th><th>Avg</th><th>Min</th><th>Max</th><th>Share</th><th>Since</th><th>Gap</th><th title="Automatic picks / fair share">Picks</th></tr>
+    ${rows}`;
+
+  const f = stats.fairness;
+  fairness.textContent =
+    f.p_value == null
+      ? `${f.random_picks} automatic picks, not enough to judge fairness yet`
+      : `${f.random_picks} automatic picks: χ² ${f.chi_squared.toFixed(2)}, p = ${f.p_value.toFixed(3)} (${f.p_value < 0.05 ? "unusually skewed" : "consistent with a fair pick"})`;
+}
+
 function formatTime(seconds) {
   const mins = Math.floor(seconds / 60);
   const secs = seconds % 60;
@@ -142,27 +363,89 @@ function renderGameList() {
                            onkeydown="if(event.key === 'Enter') this.blur()"
                            style="padding: 0.25rem 0.5rem; font-size: 0.75rem; width: 200px; border: 1px solid var(--border); background: transparent; color: var(--fg); border-radius: 4px;">
                 </div>
+
+                <div style="margin-top: 0.5rem; display: flex; align-items: center; gap: 0.5rem;">
+                    <span style="font-size: 0.75rem; color: var(--muted);">Weight:</span>
+                    <input type="number"
+                           min="0"
+                           step="0.1"
+                           value="${game.weight ?? 1}"
+                           onchange="updateGameWeight(${index}, this.value)"
+                           title="Relative chance of being picked with weighted selection"
+                           style="padding: 0.25rem 0.5rem; font-size: 0.75rem; width: 80px; border: 1px solid var(--border); background: transparent; color: var(--fg); border-radius: 4px;">
+                </div>
+
+                <div style="margin-top: 0.5rem; display: flex; align-items: center; gap: 0.5rem;">
+                    <span style="font-size: 0.75rem; color: var(--muted);">Stint:</span>
+                    <input type="text"
+                           placeholder="${config.min_swap}"
+                           value="${game.min_swap ?? ""}"
+                           onchange="updateGameDuration(${index}, 'min_swap', this)"
+                           title="Minimum stint for this game, e.g. 45s or 2m30s (empty = global setting)"
+                           style="padding: 0.25rem 0.5rem; font-size: 0.75rem; width: 70px; border: 1px solid var(--border); background: transparent; color: var(--fg); border-radius: 4px;">
+                    <span style="font-size: 0.75rem; color: var(--muted);">to</span>
+                    <input type="text"
+                           placeholder="${config.max_swap}"
+                           value="${game.max_swap ?? ""}"
+                           onchange="updateGameDuration(${index}, 'max_swap', this)"
+                           title="Maximum stint for this game, e.g. 45s or 2m30s (empty = global setting)"
+                           style="padding: 0.25rem 0.5rem; font-size: 0.75rem; width: 70px; border: 1px solid var(--border); background: transparent; color: var(--fg); border-radius: 4px;">
+                </div>
+
+                <div style="margin-top: 0.5rem; display: flex; align-items: center; gap: 0.5rem;">
+                    <span style="font-size: 0.75rem; color: var(--muted);">Keys on leave:</span>
+                    <input type="text"
+                           placeholder="None"
+                           value="${escapeHtml(game.on_leave ?? "")}"
+                           onchange="updateGameKeys(${index}, 'on_leave', this)"
+                           title="${KEYS_HELP}"
+                           style="padding: 0.25rem 0.5rem; font-size: 0.75rem; width: 160px; border: 1px solid var(--border); background: transparent; color: var(--fg); border-radius: 4px;">
+                    <span style="font-size: 0.75rem; color: var(--muted);">on enter:</span>
+                    <input type="text"
+                           placeholder="None"
+                           value="${escapeHtml(game.on_enter ?? "")}"
+                           onchange="updateGameKeys(${index}, 'on_enter', this)"
+                           title="${KEYS_HELP}"
+                           style="padding: 0.25rem 0.5rem; font-size: 0.75rem; width: 160px; border: 1px solid var(--border); background: transparent; color: var(--fg); border-radius: 4px;">
+                    <select onchange="updateGame(${index}, 'key_delivery', this.value)"
+                            title="Where the keys go. Game window sends them even when the game isn't in front, but some games ignore keys sent this way"
+                            style="padding: 0.25rem 0.5rem; font-size: 0.75rem; border: 1px solid var(--border); background: transparent; color: var(--fg); border-radius: 4px;">
+                        <option value="global" ${game.key_delivery !== "window" ? "selected" : ""}>to focused window</option>
+                        <option value="window" ${game.key_delivery === "window" ? "selected" : ""}>to game window</option>
+                    </select>
+                </div>
+
+                <div style="margin-top: 0.5rem; display: flex; align-items: center; gap: 0.5rem;">
+                    <span style="font-size: 0.75rem; color: var(--muted);">Pause by:</span>
+                    <select onchange="updateGame(${index}, 'pause_method', this.value)"
+                            title="Suspending freezes the game's process while it's swapped out, for games without a pause menu. The keys aren't sent then"
+                            style="padding: 0.25rem 0.5rem; font-size: 0.75rem; border: 1px solid var(--border); background: transparent; color: var(--fg); border-radius: 4px;">
+                        <option value="keys" ${game.pause_method !== "suspend" ? "selected" : ""}>sending keys</option>
+                        <option value="suspend" ${game.pause_method === "suspend" ? "selected" : ""}>suspending the process</option>
+                    </select>
+                </div>
+
+                <div style="margin-top: 0.5rem; display: flex; align-items: center; gap: 0.5rem;">
+                    <span style="font-size: 0.75rem; color: var(--muted);">Time budget:</span>
+                    <input type="text"
+                           placeholder="None"
+                           value="${game.time_budget ?? ""}"
+                           onchange="updateGameDuration(${index}, 'time_budget', this)"
+                           title="Total play time for this game, e.g. 1h (empty = no limit)"
+                           style="padding: 0.25rem 0.5rem; font-size: 0.75rem; width: 70px; border: 1px solid var(--border); background: transparent; color: var(--fg); border-radius: 4px;">
+                </div>
             </div>
             <div class="list-item-actions">
                 <div class="toggles-row">
-                    <label class="checkbox-container">
-                        <input type="checkbox" ${game.send_esc_on_leave ? "checked" : ""} 
-                            onchange="updateGame(${index}, 'send_esc_on_leave', this.checked)">
-                        <span class="checkmark"></span>
-                        ESC on Leave
-                    </label>
-                    <label class="checkbox-container">
-                        <input type="checkbox" ${game.send_esc_on_enter ? "checked" : ""} 
-                            onchange="updateGame(${index}, 'send_esc_on_enter', this.checked)">
-                        <span class="checkmark"></span>
-                        ESC on Enter
-                    </label>
                     <label class="checkbox-container">
                         <input type="checkbox" ${game.enabled ? "checked" : ""} 
                             onchange="updateGame(${index}, 'enabled', this.checked)">
                         <span class="checkmark"></span>
                         Enabled
                     </label>
+                    <button class="btn btn-small" onclick="swapTo('${escapeHtml(game.exe_name)}')">Swap To</button>
+                    <button class="btn btn-small" onclick="queueNext('${escapeHtml(game.exe_name)}')">Queue</button>
+                    <button class="btn btn-small" onclick="completeGame(${index})">Complete</button>
                     <button class="btn btn-small" onclick="removeGame(${index})">Remove</button>
                 </div>
             </div>
@@ -175,10 +458,18 @@ function renderGameList() {
 function renderSettings() {
   if (!config) return;
 
-  document.getElementById("min-swap").value = config.min_swap_minutes;
-  document.getElementById("max-swap").value = config.max_swap_minutes;
+  document.getElementById("min-swap").value = config.min_swap;
+  document.getElementById("max-swap").value = config.max_swap;
   document.getElementById("auto-swap").checked = config.auto_swap_enabled;
+  document.getElementById("selection-strategy").value =
+    config.selection_strategy || "random";
   document.getElementById("hide-next-swap").checked = config.hide_next_swap;
+  document.getElementById("summary-template").value =
+    config.summary_template || "";
+  document.getElementById("seed-input").value = config.seed ?? "";
+  document.getElementById("schedule-ahead").value = config.schedule_ahead ?? 0;
+  document.getElementById("focus-settle").value = config.focus_settle_ms ?? 100;
+  document.getElementById("focus-timeout").value = config.focus_timeout_ms ?? 2000;
   document.getElementById("obs-host").value = config.obs_ws_host;
   document.getElementById("obs-port").value = config.obs_ws_port;
   document.getElementById("obs-password").value = config.obs_ws_password || "";
@@ -221,6 +512,24 @@ async function updateGame(index, field, value) {
   await saveConfig();
 }
 
+const KEYS_HELP =
+  "Keys sent when swapping, separated by commas, e.g. Escape or Ctrl+P, wait 200ms, Space:500ms (hold). Empty = none";
+
+async function updateGameKeys(index, field, input) {
+  const previous = config.games[index][field];
+  config.games[index][field] = input.value.trim();
+  const res = await saveConfig();
+  if (!res.ok) {
+    alert(`Invalid key sequence: ${await res.text()}`);
+    config.games[index][field] = previous;
+    input.value = previous;
+    return;
+  }
+  // Show the sequence the way the server reads it
+  config = await res.json();
+  renderGameList();
+}
+
 async function updateGameTitle(index, newTitle) {
   if (newTitle.trim() === "") return;
   config.games[index].display_name = newTitle.trim();
@@ -232,6 +541,34 @@ async function updateGameScene(index, newScene) {
   await saveConfig();
 }
 
+async function updateGameWeight(index, value) {
+  const weight = parseFloat(value);
+  config.games[index].weight = isNaN(weight) || weight < 0 ? 1 : weight;
+  await saveConfig();
+}
+
+// Accepts seconds ("45") or h/m/s strings ("2m30s"), like the config file
+function parseDuration(value) {
+  const text = value.trim();
+  if (/^\d+$/.test(text)) return `${text}s`;
+  return /^(\d+\s*[hms]\s*)+$/i.test(text) ? text : null;
+}
+
+async function updateGameDuration(index, field, input) {
+  if (!input.value.trim()) {
+    config.games[index][field] = null;
+  } else {
+    const duration = parseDuration(input.value);
+    if (!duration) {
+      alert(`Invalid duration "${input.value}" - use e.g. 45s or 2m30s`);
+      input.value = config.games[index][field] ?? "";
+      return;
+    }
+    config.games[index][field] = duration;
+  }
+  await saveConfig();
+}
+
 async function removeGame(index) {
   config.games.splice(index, 1);
   await saveConfig();
@@ -249,9 +586,10 @@ async function addGame(exeName, title) {
   config.games.push({
     exe_name: exeName,
     display_name: title || exeName.replace(".exe", ""),
-    send_esc_on_leave: true,
-    send_esc_on_enter: true,
+    on_leave: "Escape",
+    on_enter: "Escape",
     enabled: true,
+    weight: 1,
   });
 
   await saveConfig();
@@ -259,7 +597,7 @@ async function addGame(exeName, title) {
 }
 
 async function saveConfig() {
-  await fetch("/api/config", {
+  return fetch("/api/config", {
     method: "PUT",
     headers: { "Content-Type": "application/json" },
     body: JSON.stringify(config),
@@ -267,12 +605,45 @@ async function saveConfig() {
 }
 
 async function updateSettings() {
-  config.min_swap_minutes =
-    parseInt(document.getElementById("min-swap").value) || 5;
-  config.max_swap_minutes =
-    parseInt(document.getElementById("max-swap").value) || 15;
+  const minSwap = parseDuration(document.getElementById("min-swap").value);
+  const maxSwap = parseDuration(document.getElementById("max-swap").value);
+  if (!minSwap || !maxSwap) {
+    alert("Invalid swap delay - use e.g. 45s or 2m30s");
+    renderSettings();
+    return;
+  }
+  const seedText = document.getElementById("seed-input").value.trim();
+  const seed = seedText === "" ? null : Number(seedText);
+  if (seed !== null && !(Number.isSafeInteger(seed) && seed >= 0)) {
+    alert("Invalid seed - use a whole number, or leave it empty for a random one");
+    renderSettings();
+    return;
+  }
+  const scheduleAhead = Number(document.getElementById("schedule-ahead").value);
+  if (!(Number.isInteger(scheduleAhead) && scheduleAhead >= 0)) {
+    alert("Invalid schedule length - use a whole number, or 0 to turn it off");
+    renderSettings();
+    return;
+  }
+  const focusSettle = Number(document.getElementById("focus-settle").value);
+  const focusTimeout = Number(document.getElementById("focus-timeout").value);
+  if (![focusSettle, focusTimeout].every((ms) => Number.isInteger(ms) && ms >= 0)) {
+    alert("Invalid focus timing - use a whole number of milliseconds");
+    renderSettings();
+    return;
+  }
+  config.min_swap = minSwap;
+  config.max_swap = maxSwap;
+  config.seed = seed;
+  config.schedule_ahead = scheduleAhead;
+  config.focus_settle_ms = focusSettle;
+  config.focus_timeout_ms = focusTimeout;
   config.auto_swap_enabled = document.getElementById("auto-swap").checked;
+  config.selection_strategy =
+    document.getElementById("selection-strategy").value;
   config.hide_next_swap = document.getElementById("hide-next-swap").checked;
+  config.summary_template =
+    document.getElementById("summary-template").value || "{offset} {game}";
   await saveConfig();
   updateStateDisplay();
 }
@@ -281,6 +652,52 @@ async function forceSwap() {
   await fetch("/api/swap", { method: "POST" });
 }
 
+async function swapTo(exeName) {
+  await postJson("/api/swap", { exe_name: exeName });
+}
+
+async function queueNext(exeName) {
+  const exe = state && state.queued_exe === exeName ? null : exeName;
+  await postJson("/api/queue", { exe_name: exe });
+}
+
+async function moveScheduled(id, to) {
+  await postJson("/api/schedule/move", { id, to });
+}
+
+async function rerollScheduled(id) {
+  await postJson("/api/schedule/reroll", { id });
+}
+
+async function rerollSchedule() {
+  await postJson("/api/schedule/reroll", {});
+}
+
+async function completeGame(index) {
+  const game = config.games[index];
+  if (
+    !confirm(
+      `Mark ${game.display_name} as completed? It leaves the rotation for the rest of the session.`,
+    )
+  ) {
+    return;
+  }
+  await postJson("/api/complete", { exe_name: game.exe_name });
+}
+
+async function postJson(url, body) {
+  const res = await fetch(url, {
+    method: "POST",
+    headers: { "Content-Type": "application/json" },
+    body: JSON.stringify(body),
+  });
+  if (!res.ok) {
+    const err = await res.json().catch(() => ({}));
+    alert(err.error || `Request failed (${res.status})`);
+  }
+  return res;
+}
+
 async function pauseSwapper() {
   await fetch("/api/pause", { method: "POST" });
 }
@@ -289,11 +706,157 @@ async function resumeSwapper() {
   await fetch("/api/resume", { method: "POST" });
 }
 
+async function adjustTimer(seconds) {
+  await postJson("/api/timer/adjust", { seconds });
+}
+
+async function setTimer(seconds) {
+  await postJson("/api/timer/set", { seconds });
+}
+
 document.getElementById("settings-form").addEventListener("submit", (e) => {
   e.preventDefault();
   updateSettings();
 });
 
+document.getElementById("timer-form").addEventListener("submit", (e) => {
+  e.preventDefault();
+  const input = document.getElementById("timer-seconds");
+  const seconds = parseInt(input.value);
+  if (isNaN(seconds) || seconds < 0) return;
+  setTimer(seconds).then(() => (input.value = ""));
+});
+
+async function fetchSession() {
+  const res = await fetch("/api/session");
+  const session = await res.json();
+  renderSession(session.previous);
+}
+
+function renderSession(previous) {
+  const panel = document.getElementById("resume-panel");
+  if (!previous) {
+    panel.style.display = "none";
+    return;
+  }
+
+  const savedAt = new Date(previous.saved_at).toLocaleString();
+  const game = previous.current_game
+    ? ` on ${escapeHtml(previous.current_game)}`
+    : "";
+  document.getElementById("resume-summary").innerHTML =
+    `Saved ${savedAt}${game} after ${previous.swap_count} swaps.`;
+  panel.style.display = "";
+}
+
+async function resumeSession() {
+  if (
+    state &&
+    state.swap_count > 0 &&
+    !confirm("Resuming replaces the current session. Continue?")
+  ) {
+    return;
+  }
+  const res = await postJson("/api/session/resume", {});
+  if (res.ok) renderSession(null);
+}
+
+async function discardSession() {
+  await postJson("/api/session/discard", {});
+  renderSession(null);
+}
+
+// Past sessions only change when a stint closes or a new session starts
+let sessionsKey = null;
+const COMPARE_SESSIONS = 5;
+
+async function renderSessions() {
+  if (!state) return;
+  const key = `${state.session_id}:${state.history_len}`;
+  if (key === sessionsKey) return;
+  sessionsKey = key;
+
+  const res = await fetch("/api/sessions");
+  const sessions = await res.json();
+  drawSessions(sessions);
+
+  const ids = sessions.slice(0, COMPARE_SESSIONS).map((s) => s.id);
+  if (ids.length === 0) return;
+  const compare = await (
+    await fetch(`/api/sessions/compare?ids=${ids.join(",")}`)
+  ).json();
+  drawComparison(compare);
+}
+
+function drawSessions(sessions) {
+  const container = document.getElementById("session-list");
+  if (sessions.length === 0) {
+    container.innerHTML =
+      '<div class="empty-state" style="padding: 1.5rem;">No sessions yet</div>';
+    return;
+  }
+
+  container.innerHTML = sessions
+    .map((session) => {
+      const current = session.id === state.session_id;
+      const started = new Date(session.started_at).toLocaleString();
+      const status = current ? "current" : session.ended_at ? "ended" : "stopped";
+      return `
+        <div style="display: flex; justify-content: space-between; align-items: center; padding: 0.75rem 1.25rem; border-bottom: 1px solid var(--border);">
+            <div>
+                <div style="font-weight: 500; font-size: 0.875rem;">${escapeHtml(session.name)}</div>
+                <div style="font-size: 0.75rem; color: var(--muted);">${started} &middot; ${session.games.length} games &middot; ${status}</div>
+            </div>
+            <div style="font-family: var(--mono); font-size: 0.8125rem; color: var(--muted);">${formatTime(session.total_seconds)}</div>
+        </div>
+    `;
+    })
+    .join("");
+}
+
+function drawComparison(compare) {
+  const table = document.getElementById("session-compare");
+  if (compare.games.length === 0) {
+    table.innerHTML = "";
+    return;
+  }
+
+  const header = compare.sessions
+    .map((s) => `<th>${escapeHtml(s.name)}</th>`)
+    .join("");
+  const rows = compare.games
+    .map(
+      (game) =>
+        `<tr><td>${escapeHtml(game.game_name)}</td>${game.seconds
+          .map((secs) => `<td>${secs ? formatTime(secs) : "-"}</td>`)
+          .join("")}</tr>`,
+    )
+    .join("");
+  table.innerHTML = `<tr><th>Game</th>${header}</tr>${rows}`;
+}
+
+async function startNewSession(name) {
+  if (
+    state &&
+    state.swap_count > 0 &&
+    !confirm("This ends the current session and starts a new one. Continue?")
+  ) {
+    return false;
+  }
+  const res = await postJson("/api/sessions", { name });
+  return res.ok;
+}
+
+document.getElementById("new-session-form").addEventListener("submit", (e) => {
+  e.preventDefault();
+  const input = document.getElementById("new-session-name");
+  const name = input.value.trim();
+  if (!name) return;
+  startNewSession(name).then((ok) => {
+    if (ok) input.value = "";
+  });
+});
+
 document.getElementById("obs-form").addEventListener("submit", (e) => {
   e.preventDefault();
   config.obs_ws_host = document.getElementById("obs-host").value.trim() || "localhost";
@@ -305,6 +868,9 @@ document.getElementById("obs-form").addEventListener("submit", (e) => {
 fetchConfig();
 fetchState();
 fetchWindows();
+fetchSession();
+fetchStats();
 connectWS();
 
 setInterval(fetchState, 1000);
+setInterval(fetchStats, 5000);
@@ -0,0 +1,128 @@
+//! Fixtures shared by the integration tests. Each test crate uses only part
+//! of them.
+#![allow(dead_code)]
+
+use chrono::{DateTime, TimeZone, Utc};
+use donaldswap::clock::TokioClock;
+use donaldswap::config::{AppConfig, GameConfig};
+use donaldswap::input::RecordingInput;
+use donaldswap::keys::KeySequence;
+use donaldswap::process::{MockProcessBackend, Suspender};
+use donaldswap::state::{AppState, SwapHistoryItem, SwapTrigger};
+use donaldswap::swapper::Swapper;
+use donaldswap::windows::{MockWindowBackend, MockWindowConfig};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// When every test session starts.
+pub fn start() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap()
+}
+
+pub fn secs(seconds: i64) -> chrono::Duration {
+    chrono::Duration::seconds(seconds)
+}
+
+pub fn at(seconds: i64) -> DateTime<Utc> {
+    start() + secs(seconds)
+}
+
+/// A finished stint of `game` from `from` to `to` seconds into the session.
+pub fn stint(game: &str, from: i64, to: i64, trigger: SwapTrigger) -> SwapHistoryItem {
+    SwapHistoryItem {
+        game_name: game.to_string(),
+        exe_name: format!("{}.exe", game.to_lowercase()),
+        started_at: at(from),
+        ended_at: at(to),
+        duration_seconds: (to - from) as u64,
+        paused_seconds: 0,
+        trigger,
+    }
+}
+
+/// A game that gets no keys on leave or enter.
+pub fn game(exe_name: &str, display_name: &str) -> GameConfig {
+    GameConfig {
+        exe_name: exe_name.to_string(),
+        display_name: display_name.to_string(),
+        on_leave: KeySequence::default(),
+        on_enter: KeySequence::default(),
+        ..Default::default()
+    }
+}
+
+/// A swapper on a paused clock, with a fake window for every configured game.
+pub struct Harness {
+    pub start: DateTime<Utc>,
+    pub config: Arc<RwLock<AppConfig>>,
+    pub app_state: Arc<AppState>,
+    pub swapper: Arc<Swapper>,
+    pub windows: Arc<MockWindowBackend>,
+    pub input: Arc<RecordingInput>,
+    pub processes: Arc<MockProcessBackend>,
+}
+
+pub fn harness(config: AppConfig) -> Harness {
+    harness_with(config, |swapper| swapper)
+}
+
+/// Like `harness`, with `setup` adding e.g. a database or script to the swapper.
+pub fn harness_with(config: AppConfig, setup: impl FnOnce(Swapper) -> Swapper) -> Harness {
+    let start = start();
+    let app_state = Arc::new(AppState::with_clock(Arc::new(TokioClock::starting_at(
+        start,
+    ))));
+
+    let windows = Arc::new(MockWindowBackend::new());
+    for g in &config.games {
+        windows.add(MockWindowConfig {
+            exe_name: g.exe_name.clone(),
+            title: Some(g.display_name.clone()),
+            focus_fails: false,
+            focus_ignored: false,
+            pid: None,
+        });
+    }
+
+    let config = Arc::new(RwLock::new(config));
+    let input = Arc::new(RecordingInput::new());
+    let processes = Arc::new(MockProcessBackend::new());
+    let swapper = Swapper::new(
+        config.clone(),
+        app_state.clone(),
+        windows.clone(),
+        input.clone(),
+    )
+    .with_suspender(Arc::new(Suspender::new(processes.clone())));
+    let swapper = Arc::new(setup(swapper));
+
+    Harness {
+        start,
+        config,
+        app_state,
+        swapper,
+        windows,
+        input,
+        processes,
+    }
+}
+
+impl Harness {
+    pub async fn spawn(&self) {
+        let swapper = self.swapper.clone();
+        tokio::spawn(async move { swapper.run().await });
+        // Let the loop schedule its first swap
+        tokio::task::yield_now().await;
+    }
+
+    pub async fn update_config(&self, f: impl FnOnce(&mut AppConfig)) {
+        f(&mut *self.config.write().await);
+        self.swapper.config_changed().await.unwrap();
+    }
+
+    pub fn at(&self, seconds: u64, millis: u64) -> DateTime<Utc> {
+        self.start
+            + chrono::Duration::seconds(seconds as i64)
+            + chrono::Duration::milliseconds(millis as i64)
+    }
+}
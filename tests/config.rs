@@ -0,0 +1,145 @@
+use donaldswap::config::AppConfig;
+use donaldswap::duration::SwapDuration;
+use donaldswap::keys::KeySequence;
+
+#[test]
+fn parses_duration_strings() {
+    let parse = |s: &str| s.parse::<SwapDuration>().unwrap().as_secs();
+    assert_eq!(parse("45"), 45);
+    assert_eq!(parse("45s"), 45);
+    assert_eq!(parse("2m30s"), 150);
+    assert_eq!(parse("1h 5m"), 3900);
+    assert_eq!(parse("10M"), 600);
+}
+
+#[test]
+fn rejects_malformed_durations() {
+    for input in ["", "m", "5x", "2m30", "-5s"] {
+        assert!(input.parse::<SwapDuration>().is_err(), "{:?} parsed", input);
+    }
+}
+
+#[test]
+fn durations_round_trip_as_strings() {
+    for secs in [0, 45, 60, 150, 3600, 3723] {
+        let duration = SwapDuration::from_secs(secs);
+        assert_eq!(
+            duration.to_string().parse::<SwapDuration>().unwrap(),
+            duration
+        );
+    }
+    assert_eq!(SwapDuration::from_secs(150).to_string(), "2m30s");
+}
+
+#[test]
+fn reads_seconds_and_strings() {
+    let config = AppConfig::parse(
+        r#"
+        min_swap = 45
+        max_swap = "2m30s"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.min_swap, SwapDuration::from_secs(45));
+    assert_eq!(config.max_swap, SwapDuration::from_secs(150));
+}
+
+#[test]
+fn migrates_minute_based_configs() {
+    let config = AppConfig::parse(
+        r#"
+        min_swap_minutes = 2
+        max_swap_minutes = 10
+
+        [[games]]
+        exe_name = "a.exe"
+        display_name = "Game A"
+        max_swap_minutes = 30
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.min_swap, SwapDuration::from_minutes(2));
+    assert_eq!(config.max_swap, SwapDuration::from_minutes(10));
+    assert_eq!(config.games[0].min_swap, None);
+    assert_eq!(
+        config.games[0].max_swap,
+        Some(SwapDuration::from_minutes(30))
+    );
+}
+
+#[test]
+fn new_keys_win_over_legacy_ones() {
+    let config = AppConfig::parse(
+        r#"
+        min_swap_minutes = 2
+        min_swap = "30s"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.min_swap, SwapDuration::from_secs(30));
+}
+
+#[test]
+fn migrates_esc_toggles_to_key_sequences() {
+    let config = AppConfig::parse(
+        r#"
+        [[games]]
+        exe_name = "a.exe"
+        display_name = "Game A"
+        send_esc_on_leave = true
+        send_esc_on_enter = false
+
+        [[games]]
+        exe_name = "b.exe"
+        display_name = "Game B"
+        send_esc_on_leave = false
+        on_leave = "Ctrl+P"
+
+        [[games]]
+        exe_name = "c.exe"
+        display_name = "Game C"
+        "#,
+    )
+    .unwrap();
+    let keys: Vec<_> = config
+        .games
+        .iter()
+        .map(|g| (g.on_leave.to_string(), g.on_enter.to_string()))
+        .collect();
+    assert_eq!(
+        keys,
+        vec![
+            ("Escape".to_string(), "".to_string()),
+            ("Ctrl+P".to_string(), "Escape".to_string()),
+            ("Escape".to_string(), "Escape".to_string()),
+        ]
+    );
+    assert_eq!(config.games[2].on_enter, KeySequence::escape());
+
+    let saved = toml::to_string_pretty(&config).unwrap();
+    assert!(saved.contains("on_leave = \"Ctrl+P\""), "{}", saved);
+    assert!(!saved.contains("send_esc"));
+    let unknown_key = "[[games]]\nexe_name = \"a.exe\"\ndisplay_name = \"A\"\non_enter = \"Hyper\"";
+    assert!(AppConfig::parse(unknown_key).is_err());
+}
+
+#[test]
+fn saves_intervals_as_duration_strings() {
+    let config = AppConfig {
+        min_swap: SwapDuration::from_secs(45),
+        ..Default::default()
+    };
+    let saved = toml::to_string_pretty(&config).unwrap();
+    assert!(saved.contains("min_swap = \"45s\""), "{}", saved);
+    assert!(!saved.contains("min_swap_minutes"));
+}
+
+#[test]
+fn seed_is_optional() {
+    assert_eq!(AppConfig::parse("").unwrap().seed, None);
+    let config = AppConfig::parse("seed = 42").unwrap();
+    assert_eq!(config.seed, Some(42));
+
+    let saved = toml::to_string_pretty(&config).unwrap();
+    assert!(saved.contains("seed = 42"), "{}", saved);
+}
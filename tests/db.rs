@@ -0,0 +1,228 @@
+mod common;
+
+use common::{at, game, harness_with, secs, start, stint};
+use donaldswap::config::AppConfig;
+use donaldswap::db::{GameComparison, SessionDb, SessionGame};
+use donaldswap::duration::SwapDuration;
+use donaldswap::state::{CompletedGame, SwapHistoryItem, SwapState, SwapTrigger};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn config() -> AppConfig {
+    let mut disabled = game("c.exe", "Game C");
+    disabled.enabled = false;
+    AppConfig {
+        games: vec![game("a.exe", "Game A"), game("b.exe", "Game B"), disabled],
+        min_swap: SwapDuration::from_minutes(1),
+        max_swap: SwapDuration::from_minutes(1),
+        ..Default::default()
+    }
+}
+
+fn session_state(id: i64, history: Vec<SwapHistoryItem>) -> SwapState {
+    SwapState {
+        session_id: Some(id),
+        history,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn sessions_remember_their_enabled_games() {
+    let db = SessionDb::open_in_memory().unwrap();
+    let id = db.create_session("Day 1", start(), &config()).unwrap();
+
+    let session = db.session(id).unwrap().unwrap();
+    assert_eq!(session.name, "Day 1");
+    assert_eq!(session.started_at, start());
+    assert_eq!(session.ended_at, None);
+    assert_eq!(
+        session.games,
+        vec![
+            SessionGame {
+                exe_name: "a.exe".to_string(),
+                display_name: "Game A".to_string(),
+            },
+            SessionGame {
+                exe_name: "b.exe".to_string(),
+                display_name: "Game B".to_string(),
+            },
+        ]
+    );
+    assert_eq!(db.session(id + 1).unwrap(), None);
+}
+
+#[test]
+fn sync_only_adds_what_is_new() {
+    let db = SessionDb::open_in_memory().unwrap();
+    let id = db.create_session("Day 1", start(), &config()).unwrap();
+
+    let mut state = session_state(id, vec![stint("Game A", 0, 60, SwapTrigger::Automatic)]);
+    db.sync(&state).unwrap();
+    db.sync(&state).unwrap();
+
+    state
+        .history
+        .push(stint("Game B", 60, 100, SwapTrigger::Automatic));
+    state
+        .history
+        .push(stint("Game A", 100, 130, SwapTrigger::Automatic));
+    state.completed.push(CompletedGame {
+        game_name: "Game A".to_string(),
+        exe_name: "game_a.exe".to_string(),
+        completed_at: start() + secs(130),
+        total_seconds: 90,
+    });
+    state.session_ended_at = Some(start() + secs(130));
+    db.sync(&state).unwrap();
+
+    let session = db.session(id).unwrap().unwrap();
+    assert_eq!(session.stints, 3);
+    assert_eq!(session.total_seconds, 130);
+    assert_eq!(session.ended_at, Some(start() + secs(130)));
+    assert_eq!(db.completions(id).unwrap(), state.completed);
+
+    let (total, items) = db.history(id, 0, 2).unwrap();
+    assert_eq!(total, 3);
+    assert_eq!(
+        items,
+        vec![
+            stint("Game A", 100, 130, SwapTrigger::Automatic),
+            stint("Game B", 60, 100, SwapTrigger::Automatic)
+        ]
+    );
+
+    let totals: Vec<_> = db
+        .totals(id)
+        .unwrap()
+        .into_iter()
+        .map(|t| (t.game_name, t.total_seconds, t.stints))
+        .collect();
+    assert_eq!(
+        totals,
+        vec![("Game A".to_string(), 90, 2), ("Game B".to_string(), 40, 1)]
+    );
+}
+
+#[test]
+fn compare_lines_up_per_game_time_by_session() {
+    let db = SessionDb::open_in_memory().unwrap();
+    let first = db.create_session("Day 1", start(), &config()).unwrap();
+    let second = db
+        .create_session("Day 2", start() + secs(86400), &config())
+        .unwrap();
+
+    db.sync(&session_state(
+        first,
+        vec![
+            stint("Game A", 0, 60, SwapTrigger::Automatic),
+            stint("Game B", 60, 90, SwapTrigger::Automatic),
+        ],
+    ))
+    .unwrap();
+    db.sync(&session_state(
+        second,
+        vec![stint("Game B", 0, 100, SwapTrigger::Automatic)],
+    ))
+    .unwrap();
+
+    let sessions: Vec<_> = db.sessions().unwrap().into_iter().map(|s| s.id).collect();
+    assert_eq!(sessions, vec![second, first]);
+
+    assert_eq!(
+        db.compare(&[first, second]).unwrap(),
+        vec![
+            GameComparison {
+                game_name: "Game B".to_string(),
+                seconds: vec![30, 100],
+            },
+            GameComparison {
+                game_name: "Game A".to_string(),
+                seconds: vec![60, 0],
+            },
+        ]
+    );
+}
+
+#[test]
+fn only_empty_sessions_are_removed() {
+    let db = SessionDb::open_in_memory().unwrap();
+    let empty = db.create_session("Empty", start(), &config()).unwrap();
+    let played = db.create_session("Played", start(), &config()).unwrap();
+    db.sync(&session_state(
+        played,
+        vec![stint("Game A", 0, 60, SwapTrigger::Automatic)],
+    ))
+    .unwrap();
+
+    db.remove_if_empty(empty).unwrap();
+    db.remove_if_empty(played).unwrap();
+
+    let names: Vec<_> = db.sessions().unwrap().into_iter().map(|s| s.name).collect();
+    assert_eq!(names, vec!["Played".to_string()]);
+}
+
+#[test]
+fn finished_sessions_are_reopened_when_resumed() {
+    let db = SessionDb::open_in_memory().unwrap();
+    let id = db.create_session("Day 1", start(), &config()).unwrap();
+    let state = SwapState {
+        current_game: Some("Game B".to_string()),
+        current_exe: Some("b.exe".to_string()),
+        last_swap_at: Some(at(60)),
+        ..session_state(id, vec![stint("Game A", 0, 60, SwapTrigger::Automatic)])
+    };
+
+    // Shut down while Game B is running
+    db.finish(&state, at(100)).unwrap();
+    let session = db.session(id).unwrap().unwrap();
+    assert_eq!(session.ended_at, Some(at(100)));
+    assert_eq!(session.stints, 2);
+    assert_eq!(session.total_seconds, 100);
+
+    // Resumed, Game B's stint is running again
+    db.sync(&state).unwrap();
+    let session = db.session(id).unwrap().unwrap();
+    assert_eq!(session.ended_at, None);
+    assert_eq!(session.stints, 1);
+    assert_eq!(session.total_seconds, 60);
+}
+
+#[tokio::test(start_paused = true)]
+async fn swapper_records_stints_and_starts_new_sessions() {
+    let db = Arc::new(SessionDb::open_in_memory().unwrap());
+    let config = config();
+    let first = db.create_session("Day 1", start(), &config).unwrap();
+
+    let h = harness_with(config.clone(), |swapper| {
+        swapper.with_session_db(db.clone())
+    });
+    h.app_state
+        .update_state(|s| {
+            s.session_id = Some(first);
+            s.session_name = Some("Day 1".to_string());
+        })
+        .await;
+    h.spawn().await;
+
+    h.swapper.force_swap().await.unwrap();
+    tokio::time::sleep(Duration::from_secs(150)).await;
+    assert_eq!(db.session(first).unwrap().unwrap().stints, 2);
+
+    let second = db
+        .create_session("Day 2", h.app_state.clock.now(), &config)
+        .unwrap();
+    h.swapper.new_session(second, "Day 2").await.unwrap();
+
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.session_id, Some(second));
+    assert_eq!(state.session_name.as_deref(), Some("Day 2"));
+    assert_eq!(state.swap_count, 0);
+    assert!(state.history.is_empty());
+    assert!(state.current_game.is_some());
+
+    let first = db.session(first).unwrap().unwrap();
+    assert_eq!(first.stints, 3);
+    assert!(first.ended_at.is_some());
+    assert_eq!(db.session(second).unwrap().unwrap().stints, 0);
+}
@@ -0,0 +1,101 @@
+mod common;
+
+use common::{at, start, stint};
+use donaldswap::export::{format_offset, GameTotal, SessionExport, DEFAULT_SUMMARY_TEMPLATE};
+use donaldswap::state::{SwapState, SwapTrigger};
+use std::collections::HashMap;
+
+/// Two finished stints and a third still running.
+fn session() -> SwapState {
+    SwapState {
+        current_game: Some("Celeste".to_string()),
+        current_exe: Some("celeste.exe".to_string()),
+        last_swap_at: Some(at(4000)),
+        swap_count: 3,
+        history: vec![
+            stint("Celeste", 0, 600, SwapTrigger::Automatic),
+            stint("Hollow, Knight", 600, 4000, SwapTrigger::Forced),
+        ],
+        total_times: HashMap::from([
+            ("Celeste".to_string(), 600),
+            ("Hollow, Knight".to_string(), 3400),
+        ]),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn export_includes_the_stint_in_progress() {
+    let export = SessionExport::new(&session(), at(4300));
+
+    assert_eq!(export.started_at, Some(start()));
+    assert_eq!(export.history.len(), 3);
+    assert_eq!(export.history[2].ended_at, at(4300));
+    assert_eq!(
+        export.totals,
+        vec![
+            GameTotal {
+                game_name: "Hollow, Knight".to_string(),
+                total_seconds: 3400,
+                stints: 1,
+            },
+            GameTotal {
+                game_name: "Celeste".to_string(),
+                total_seconds: 900,
+                stints: 2,
+            },
+        ]
+    );
+}
+
+#[test]
+fn csv_quotes_fields_with_commas() {
+    let export = SessionExport::new(&session(), at(4300));
+
+    let history = export.history_csv();
+    let lines: Vec<&str> = history.lines().collect();
+    assert_eq!(
+        lines[0],
+        "game_name,exe_name,started_at,ended_at,duration_seconds,paused_seconds,trigger"
+    );
+    assert_eq!(
+        lines[2],
+        "\"Hollow, Knight\",\"hollow, knight.exe\",2024-01-01T12:10:00Z,2024-01-01T13:06:40Z,3400,0,forced"
+    );
+
+    assert_eq!(
+        export.totals_csv(),
+        "game_name,total_seconds,stints\n\"Hollow, Knight\",3400,1\nCeleste,900,2\n"
+    );
+}
+
+#[test]
+fn summary_lists_chapters_from_session_start() {
+    let export = SessionExport::new(&session(), at(4300));
+
+    assert_eq!(
+        export.summary(DEFAULT_SUMMARY_TEMPLATE),
+        "0:00 Celeste\n10:00 Hollow, Knight\n1:06:40 Celeste\n"
+    );
+    assert_eq!(
+        export.summary("{index}. {game} for {duration} ({trigger})"),
+        "1. Celeste for 10:00 (automatic)\n\
+         2. Hollow, Knight for 56:40 (forced)\n\
+         3. Celeste for 5:00 (automatic)\n"
+    );
+}
+
+#[test]
+fn empty_session_has_no_chapters() {
+    let export = SessionExport::new(&SwapState::default(), start());
+    assert_eq!(export.summary(DEFAULT_SUMMARY_TEMPLATE), "");
+    assert_eq!(export.history_csv().lines().count(), 1);
+}
+
+#[test]
+fn offsets_use_chapter_format() {
+    assert_eq!(format_offset(0), "0:00");
+    assert_eq!(format_offset(65), "1:05");
+    assert_eq!(format_offset(3600), "1:00:00");
+    assert_eq!(format_offset(36_000 + 61), "10:01:01");
+}
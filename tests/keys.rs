@@ -0,0 +1,116 @@
+use chrono::Utc;
+use donaldswap::clock::{Clock, TokioClock};
+use donaldswap::input::{play, InputBackend};
+use donaldswap::keys::{Key, KeySequence, KeyStep};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+#[test]
+fn parses_chords_holds_and_waits() {
+    let sequence: KeySequence = "ctrl+shift+p, wait 200ms, Space:1.5s, F1".parse().unwrap();
+    assert_eq!(
+        sequence.steps(),
+        &[
+            KeyStep::Press {
+                keys: vec![Key::Ctrl, Key::Shift, Key::Char('p')],
+                hold: Duration::ZERO,
+            },
+            KeyStep::Wait(Duration::from_millis(200)),
+            KeyStep::Press {
+                keys: vec![Key::Space],
+                hold: Duration::from_millis(1500),
+            },
+            KeyStep::Press {
+                keys: vec![Key::Function(1)],
+                hold: Duration::ZERO,
+            },
+        ]
+    );
+    assert_eq!(
+        sequence.to_string(),
+        "Ctrl+Shift+P, wait 200ms, Space:1500ms, F1"
+    );
+}
+
+#[test]
+fn sequences_round_trip_as_strings() {
+    for input in [
+        "",
+        "Escape",
+        "Alt+Enter:100ms",
+        "Comma, Plus, Colon:2s",
+        "Meta+Tab, wait 1s, Escape",
+    ] {
+        let sequence: KeySequence = input.parse().unwrap();
+        assert_eq!(sequence.to_string(), input);
+    }
+    assert_eq!("esc".parse::<KeySequence>().unwrap(), KeySequence::escape());
+}
+
+#[test]
+fn rejects_unknown_keys_and_bad_delays() {
+    for input in [
+        "Hyper",
+        "F21",
+        "Ctrl+",
+        "P:soon",
+        "wait",
+        "wait 5",
+        "Escape,,P",
+    ] {
+        assert!(input.parse::<KeySequence>().is_err(), "{:?} parsed", input);
+    }
+}
+
+#[derive(Default)]
+struct Recorder {
+    events: Mutex<Vec<(String, u128)>>,
+    start: Mutex<Option<Instant>>,
+}
+
+impl Recorder {
+    fn record(&self, event: String) {
+        let now = Instant::now();
+        let start = *self.start.lock().unwrap().get_or_insert(now);
+        self.events
+            .lock()
+            .unwrap()
+            .push((event, (now - start).as_millis()));
+    }
+}
+
+impl InputBackend for Recorder {
+    fn key_down(&self, key: Key) -> anyhow::Result<()> {
+        self.record(format!("{} down", key));
+        Ok(())
+    }
+
+    fn key_up(&self, key: Key) -> anyhow::Result<()> {
+        self.record(format!("{} up", key));
+        Ok(())
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn play_holds_and_waits_on_the_clock() {
+    let clock: Arc<dyn Clock> = Arc::new(TokioClock::starting_at(Utc::now()));
+    let input = Recorder::default();
+    let sequence: KeySequence = "Ctrl+P:500ms, wait 200ms, Escape".parse().unwrap();
+
+    play(&input, &*clock, &sequence).await.unwrap();
+
+    let events = input.events.lock().unwrap().clone();
+    let expected: Vec<(String, u128)> = [
+        ("Ctrl down", 0),
+        ("P down", 0),
+        ("P up", 500),
+        ("Ctrl up", 500),
+        ("Escape down", 700),
+        ("Escape up", 700),
+    ]
+    .into_iter()
+    .map(|(e, t)| (e.to_string(), t))
+    .collect();
+    assert_eq!(events, expected);
+}
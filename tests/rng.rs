@@ -0,0 +1,46 @@
+use donaldswap::rng::{RngPosition, SwapRng, MAX_RANDOM_SEED};
+use rand::Rng;
+
+fn draws(rng: &mut impl Rng) -> Vec<u32> {
+    (0..8).map(|_| rng.gen_range(0..1000)).collect()
+}
+
+#[test]
+fn same_seed_gives_the_same_draws() {
+    let mut a = SwapRng::new(42);
+    let mut b = SwapRng::new(42);
+    assert_eq!(draws(a.delays()), draws(b.delays()));
+    assert_eq!(draws(a.picks()), draws(b.picks()));
+    assert_ne!(
+        draws(SwapRng::new(43).delays()),
+        draws(SwapRng::new(42).delays())
+    );
+}
+
+#[test]
+fn extra_delay_draws_do_not_shift_the_picks() {
+    let mut a = SwapRng::new(7);
+    let mut b = SwapRng::new(7);
+    draws(b.delays());
+    assert_eq!(draws(a.picks()), draws(b.picks()));
+}
+
+#[test]
+fn position_resumes_the_same_sequence() {
+    let mut rng = SwapRng::new(1234);
+    draws(rng.delays());
+    draws(rng.picks());
+    draws(rng.picks());
+
+    let mut resumed = SwapRng::at(rng.seed(), rng.position());
+    assert_eq!(draws(resumed.delays()), draws(rng.delays()));
+    assert_eq!(draws(resumed.picks()), draws(rng.picks()));
+    assert_eq!(SwapRng::new(5).position(), RngPosition::default());
+}
+
+#[test]
+fn random_seeds_fit_in_a_javascript_number() {
+    for _ in 0..100 {
+        assert!(SwapRng::from_entropy().seed() <= MAX_RANDOM_SEED);
+    }
+}
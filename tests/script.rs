@@ -0,0 +1,178 @@
+mod common;
+
+use common::{game, harness_with, Harness};
+use donaldswap::config::AppConfig;
+use donaldswap::duration::SwapDuration;
+use donaldswap::script::{ScriptEnd, SwapScript};
+use donaldswap::state::SwapTrigger;
+use std::time::Duration;
+
+fn config() -> AppConfig {
+    AppConfig {
+        games: vec![game("a.exe", "Game A"), game("b.exe", "Game B")],
+        min_swap: SwapDuration::from_minutes(1),
+        max_swap: SwapDuration::from_minutes(1),
+        schedule_ahead: 2,
+        ..Default::default()
+    }
+}
+
+fn played(script: &SwapScript) -> Vec<(&str, u64)> {
+    script
+        .entries
+        .iter()
+        .map(|e| (e.game_name.as_str(), e.duration.as_secs()))
+        .collect()
+}
+
+#[test]
+fn toml_scripts_match_games_by_exe_or_name() {
+    let script = SwapScript::parse_toml(
+        r#"
+at_end = "loop"
+
+[[swaps]]
+game = "A.EXE"
+duration = "7m"
+
+[[swaps]]
+game = "game b"
+duration = 180
+
+[[swaps]]
+game = "a.exe"
+duration = "1m30s"
+"#,
+        &config(),
+    )
+    .unwrap();
+
+    assert_eq!(script.at_end, ScriptEnd::Loop);
+    assert_eq!(
+        played(&script),
+        vec![("Game A", 420), ("Game B", 180), ("Game A", 90)]
+    );
+    assert_eq!(script.entries[0].exe_name, "a.exe");
+}
+
+#[test]
+fn csv_scripts_skip_headers_and_comments() {
+    let script = SwapScript::parse_csv(
+        "game,duration\n# warm-up\na.exe,7m\n\n\"Game B\", 3m\n",
+        &config(),
+    )
+    .unwrap();
+
+    assert_eq!(script.at_end, ScriptEnd::Stop);
+    assert_eq!(played(&script), vec![("Game A", 420), ("Game B", 180)]);
+}
+
+#[test]
+fn invalid_entries_are_all_reported() {
+    let err = SwapScript::parse_csv(
+        "game,duration\nc.exe,7m\na.exe,0s\nb.exe,soon\na.exe\nb.exe,1m\n",
+        &config(),
+    )
+    .unwrap_err()
+    .to_string();
+
+    assert!(err.contains("line 2: unknown game \"c.exe\""), "{}", err);
+    assert!(
+        err.contains("line 3: duration must be more than zero"),
+        "{}",
+        err
+    );
+    assert!(err.contains("line 4: invalid duration \"soon\""), "{}", err);
+    assert!(err.contains("line 5: missing duration"), "{}", err);
+    assert!(!err.contains("line 6"), "{}", err);
+
+    assert!(SwapScript::parse_toml("", &config()).is_err());
+    assert!(SwapScript::parse_toml("[[swaps]]\ngame = \"a.exe\"\n", &config()).is_err());
+}
+
+#[test]
+fn looping_scripts_wrap_around() {
+    let script = SwapScript::parse_csv("a.exe,1m\nb.exe,2m\n", &config()).unwrap();
+    assert_eq!(script.entry(1).unwrap().exe_name, "b.exe");
+    assert_eq!(script.entry(2), None);
+
+    let script = script.with_end(ScriptEnd::Loop);
+    assert_eq!(script.entry(2).unwrap().exe_name, "a.exe");
+    assert_eq!(script.entry(5).unwrap().exe_name, "b.exe");
+}
+
+async fn run_script(script: SwapScript) -> Harness {
+    let h = harness_with(config(), |swapper| swapper.with_script(script));
+    h.spawn().await;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    h
+}
+
+#[tokio::test(start_paused = true)]
+async fn scripts_play_back_as_written_then_stop() {
+    let script = SwapScript::parse_csv("a.exe,7m\nb.exe,3m\na.exe,10m\n", &config()).unwrap();
+    let h = run_script(script).await;
+
+    // The first game comes up right away
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.current_exe.as_deref(), Some("a.exe"));
+    let upcoming: Vec<_> = state
+        .schedule
+        .iter()
+        .map(|e| (e.id, e.duration_seconds))
+        .collect();
+    assert_eq!(upcoming, vec![(1, 180), (2, 600)]);
+    assert!(h.swapper.reroll_scheduled(None).await.is_err());
+
+    tokio::time::sleep(Duration::from_secs(60 * 30)).await;
+    let state = h.app_state.get_state().await;
+    assert!(state.session_ended_at.is_some());
+    let history: Vec<_> = state
+        .history
+        .iter()
+        .map(|h| (h.exe_name.as_str(), h.duration_seconds, h.trigger))
+        .collect();
+    assert_eq!(
+        history,
+        vec![
+            ("a.exe", 420, SwapTrigger::Scripted),
+            ("b.exe", 180, SwapTrigger::Scripted),
+            ("a.exe", 600, SwapTrigger::Scripted),
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn looping_scripts_keep_going() {
+    let script = SwapScript::parse_csv("a.exe,1m\nb.exe,2m\n", &config())
+        .unwrap()
+        .with_end(ScriptEnd::Loop);
+    let h = run_script(script).await;
+
+    tokio::time::sleep(Duration::from_secs(60 * 10)).await;
+    let state = h.app_state.get_state().await;
+    assert!(state.session_ended_at.is_none());
+    assert_eq!(state.script_position, 7);
+    let history: Vec<_> = state.history.iter().map(|h| h.duration_seconds).collect();
+    assert_eq!(history, vec![60, 120, 60, 120, 60, 120]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn scripted_games_out_of_the_rotation_are_skipped() {
+    let script =
+        SwapScript::parse_csv("a.exe,5m\nb.exe,5m\na.exe,3m\nb.exe,5m\n", &config()).unwrap();
+    let h = run_script(script).await;
+
+    h.swapper.complete("b.exe").await.unwrap();
+    tokio::time::sleep(Duration::from_secs(60 * 30)).await;
+
+    let state = h.app_state.get_state().await;
+    assert!(state.session_ended_at.is_some());
+    assert_eq!(state.script_position, 4);
+    let history: Vec<_> = state
+        .history
+        .iter()
+        .map(|h| (h.exe_name.as_str(), h.duration_seconds))
+        .collect();
+    assert_eq!(history, vec![("a.exe", 300), ("a.exe", 180)]);
+}
@@ -0,0 +1,303 @@
+use donaldswap::config::{GameConfig, SelectionStrategy};
+use donaldswap::selection::{SelectionContext, Selector};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::collections::{HashMap, HashSet};
+
+fn game(exe_name: &str, weight: f64) -> GameConfig {
+    GameConfig {
+        exe_name: exe_name.to_string(),
+        display_name: exe_name.trim_end_matches(".exe").to_string(),
+        weight,
+        ..Default::default()
+    }
+}
+
+fn games(names: &[&str]) -> Vec<GameConfig> {
+    names.iter().map(|n| game(n, 1.0)).collect()
+}
+
+fn rng() -> StdRng {
+    StdRng::seed_from_u64(7)
+}
+
+/// Runs `count` automatic swaps, excluding the current game each time like the swapper does.
+fn rotate(
+    selector: &mut Selector,
+    strategy: SelectionStrategy,
+    games: &[GameConfig],
+    start: Option<&str>,
+    count: usize,
+) -> Vec<String> {
+    let all: Vec<&GameConfig> = games.iter().collect();
+    let no_times = HashMap::new();
+    let mut rng = rng();
+    let mut current = start.map(|s| s.to_string());
+    let mut picks = Vec::new();
+
+    for _ in 0..count {
+        let candidates: Vec<&GameConfig> = all
+            .iter()
+            .filter(|g| Some(&g.exe_name) != current.as_ref())
+            .copied()
+            .collect();
+        let ctx = SelectionContext {
+            games: &all,
+            candidates: &candidates,
+            current_exe: current.as_deref(),
+            total_times: &no_times,
+        };
+        let picked = selector.pick(strategy, &ctx, &mut rng).unwrap();
+        current = Some(picked.exe_name.clone());
+        picks.push(picked.exe_name.clone());
+    }
+    picks
+}
+
+fn pick_once<'g>(
+    strategy: SelectionStrategy,
+    games: &[&'g GameConfig],
+    candidates: &[&'g GameConfig],
+    current_exe: Option<&str>,
+    total_times: &HashMap<String, u64>,
+    rng: &mut StdRng,
+) -> Option<&'g GameConfig> {
+    let ctx = SelectionContext {
+        games,
+        candidates,
+        current_exe,
+        total_times,
+    };
+    Selector::new().pick(strategy, &ctx, rng)
+}
+
+#[test]
+fn no_candidates_picks_nothing() {
+    let games = games(&["a.exe"]);
+    let all: Vec<&GameConfig> = games.iter().collect();
+    let pick = pick_once(
+        SelectionStrategy::Random,
+        &all,
+        &[],
+        Some("a.exe"),
+        &HashMap::new(),
+        &mut rng(),
+    );
+    assert!(pick.is_none());
+}
+
+#[test]
+fn random_only_picks_candidates_and_reaches_all_of_them() {
+    let games = games(&["a.exe", "b.exe", "c.exe", "d.exe"]);
+    let all: Vec<&GameConfig> = games.iter().collect();
+    let candidates = &all[1..];
+    let mut rng = rng();
+
+    let mut seen = HashSet::new();
+    for _ in 0..200 {
+        let pick = pick_once(
+            SelectionStrategy::Random,
+            &all,
+            candidates,
+            Some("a.exe"),
+            &HashMap::new(),
+            &mut rng,
+        )
+        .unwrap();
+        assert_ne!(pick.exe_name, "a.exe");
+        seen.insert(pick.exe_name.clone());
+    }
+    assert_eq!(seen.len(), 3);
+}
+
+#[test]
+fn weighted_follows_the_weights() {
+    let games = [game("a.exe", 3.0), game("b.exe", 1.0), game("c.exe", 0.0)];
+    let all: Vec<&GameConfig> = games.iter().collect();
+    let mut rng = rng();
+
+    let mut counts: HashMap<String, u32> = HashMap::new();
+    for _ in 0..10_000 {
+        let pick = pick_once(
+            SelectionStrategy::Weighted,
+            &all,
+            &all,
+            None,
+            &HashMap::new(),
+            &mut rng,
+        )
+        .unwrap();
+        *counts.entry(pick.exe_name.clone()).or_default() += 1;
+    }
+
+    assert_eq!(counts.get("c.exe"), None);
+    let a = counts["a.exe"] as f64;
+    let b = counts["b.exe"] as f64;
+    assert!((2.7..3.3).contains(&(a / b)), "ratio was {}", a / b);
+}
+
+#[test]
+fn weighted_with_all_zero_weights_is_fair() {
+    let games = [game("a.exe", 0.0), game("b.exe", 0.0)];
+    let all: Vec<&GameConfig> = games.iter().collect();
+    let mut rng = rng();
+
+    let mut seen = HashSet::new();
+    for _ in 0..100 {
+        let pick = pick_once(
+            SelectionStrategy::Weighted,
+            &all,
+            &all,
+            None,
+            &HashMap::new(),
+            &mut rng,
+        )
+        .unwrap();
+        seen.insert(pick.exe_name.clone());
+    }
+    assert_eq!(seen.len(), 2);
+}
+
+#[test]
+fn round_robin_follows_config_order() {
+    let games = games(&["a.exe", "b.exe", "c.exe"]);
+    let picks = rotate(
+        &mut Selector::new(),
+        SelectionStrategy::RoundRobin,
+        &games,
+        None,
+        7,
+    );
+    assert_eq!(
+        picks,
+        ["a.exe", "b.exe", "c.exe", "a.exe", "b.exe", "c.exe", "a.exe"]
+    );
+}
+
+#[test]
+fn round_robin_skips_unavailable_games() {
+    let games = games(&["a.exe", "b.exe", "c.exe", "d.exe"]);
+    let all: Vec<&GameConfig> = games.iter().collect();
+    // c.exe has no window open
+    let candidates = vec![all[0], all[3]];
+
+    let pick = pick_once(
+        SelectionStrategy::RoundRobin,
+        &all,
+        &candidates,
+        Some("b.exe"),
+        &HashMap::new(),
+        &mut rng(),
+    )
+    .unwrap();
+    assert_eq!(pick.exe_name, "d.exe");
+}
+
+#[test]
+fn shuffle_bag_plays_every_game_before_repeating() {
+    let games = games(&["a.exe", "b.exe", "c.exe", "d.exe", "e.exe"]);
+    let picks = rotate(
+        &mut Selector::new(),
+        SelectionStrategy::ShuffleBag,
+        &games,
+        None,
+        15,
+    );
+
+    for round in picks.chunks(5) {
+        let unique: HashSet<&String> = round.iter().collect();
+        assert_eq!(unique.len(), 5, "round repeated a game: {:?}", round);
+    }
+    for pair in picks.windows(2) {
+        assert_ne!(pair[0], pair[1]);
+    }
+}
+
+#[test]
+fn shuffle_bag_counts_games_played_by_other_means() {
+    let games = games(&["a.exe", "b.exe", "c.exe"]);
+    let all: Vec<&GameConfig> = games.iter().collect();
+    let no_times = HashMap::new();
+    let mut rng = rng();
+    let mut selector = Selector::new();
+
+    // Start a round, then force a swap to whichever game is still left
+    let ctx = SelectionContext {
+        games: &all,
+        candidates: &all,
+        current_exe: None,
+        total_times: &no_times,
+    };
+    let first = selector
+        .pick(SelectionStrategy::ShuffleBag, &ctx, &mut rng)
+        .unwrap()
+        .exe_name
+        .clone();
+    let forced = all.iter().find(|g| g.exe_name != first).unwrap();
+    selector.played(&forced.exe_name);
+
+    let candidates: Vec<&GameConfig> = all
+        .iter()
+        .filter(|g| g.exe_name != forced.exe_name)
+        .copied()
+        .collect();
+    let ctx = SelectionContext {
+        games: &all,
+        candidates: &candidates,
+        current_exe: Some(&forced.exe_name),
+        total_times: &no_times,
+    };
+    let next = selector
+        .pick(SelectionStrategy::ShuffleBag, &ctx, &mut rng)
+        .unwrap();
+    assert_ne!(next.exe_name, first);
+    assert_ne!(next.exe_name, forced.exe_name);
+}
+
+#[test]
+fn least_played_picks_the_game_with_least_time() {
+    let games = games(&["a.exe", "b.exe", "c.exe"]);
+    let all: Vec<&GameConfig> = games.iter().collect();
+    let totals = HashMap::from([
+        ("a".to_string(), 600),
+        ("b".to_string(), 120),
+        ("c".to_string(), 300),
+    ]);
+
+    let pick = pick_once(
+        SelectionStrategy::LeastPlayed,
+        &all,
+        &all,
+        None,
+        &totals,
+        &mut rng(),
+    )
+    .unwrap();
+    assert_eq!(pick.exe_name, "b.exe");
+}
+
+#[test]
+fn least_played_treats_unplayed_games_as_zero_and_breaks_ties() {
+    let games = games(&["a.exe", "b.exe", "c.exe"]);
+    let all: Vec<&GameConfig> = games.iter().collect();
+    let totals = HashMap::from([("a".to_string(), 600)]);
+    let mut rng = rng();
+
+    let mut seen = HashSet::new();
+    for _ in 0..100 {
+        let pick = pick_once(
+            SelectionStrategy::LeastPlayed,
+            &all,
+            &all,
+            None,
+            &totals,
+            &mut rng,
+        )
+        .unwrap();
+        seen.insert(pick.exe_name.clone());
+    }
+    assert_eq!(
+        seen,
+        HashSet::from(["b.exe".to_string(), "c.exe".to_string()])
+    );
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use common::{secs, start};
+use donaldswap::clock::TokioClock;
+use donaldswap::session::{autosave, SessionSnapshot, SessionStore};
+use donaldswap::state::{AppState, SwapState};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn temp_path() -> PathBuf {
+    std::env::temp_dir().join(format!("donaldswap-session-{}.json", uuid::Uuid::new_v4()))
+}
+
+fn mid_stint() -> SwapState {
+    SwapState {
+        current_game: Some("Game A".to_string()),
+        current_exe: Some("a.exe".to_string()),
+        last_swap_at: Some(start()),
+        next_swap_at: Some(start() + secs(300)),
+        swap_count: 3,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn downtime_does_not_count_towards_the_stint_or_countdown() {
+    let snapshot = SessionSnapshot {
+        saved_at: start() + secs(100),
+        state: mid_stint(),
+    };
+
+    let now = start() + secs(1000);
+    let state = snapshot.into_state(now);
+    assert!(!state.is_paused);
+    assert_eq!(state.next_swap_at, Some(start() + secs(1200)));
+    assert_eq!(state.stint_duration(now), Some(secs(100)));
+    assert_eq!(state.time_until_swap(now), Some(secs(200)));
+}
+
+#[test]
+fn paused_session_resumes_paused() {
+    let mut state = mid_stint();
+    state.pause(start() + secs(50));
+    let snapshot = SessionSnapshot {
+        saved_at: start() + secs(100),
+        state,
+    };
+
+    let now = start() + secs(1000);
+    let state = snapshot.into_state(now);
+    assert!(state.is_paused);
+    assert_eq!(state.stint_duration(now), Some(secs(50)));
+    assert_eq!(state.time_until_swap(now), Some(secs(250)));
+}
+
+#[tokio::test]
+async fn store_round_trips_snapshots() {
+    let path = temp_path();
+    let store = SessionStore::new(path.clone());
+    assert_eq!(store.load().unwrap(), None);
+
+    let snapshot = SessionSnapshot {
+        saved_at: start(),
+        state: mid_stint(),
+    };
+    store.save(&snapshot).await.unwrap();
+    assert_eq!(store.load().unwrap(), Some(snapshot));
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn autosave_writes_on_every_swap() {
+    let path = temp_path();
+    let store = Arc::new(SessionStore::new(path.clone()));
+    let app_state = Arc::new(AppState::with_clock(Arc::new(TokioClock::starting_at(
+        start(),
+    ))));
+    tokio::spawn(autosave(
+        store.clone(),
+        app_state.clone(),
+        Duration::from_secs(3600),
+    ));
+
+    // A fresh session with nothing in it doesn't overwrite the saved one
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(store.load().unwrap(), None);
+
+    app_state
+        .update_state(|s| {
+            s.current_game = Some("Game A".to_string());
+            s.swap_count = 1;
+        })
+        .await;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    let saved = store.load().unwrap().unwrap();
+    assert_eq!(saved.state.swap_count, 1);
+    assert_eq!(saved.saved_at, start() + secs(1));
+
+    std::fs::remove_file(path).unwrap();
+}
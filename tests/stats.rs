@@ -0,0 +1,148 @@
+mod common;
+
+use common::{game, secs, start, stint};
+use donaldswap::config::{AppConfig, SelectionStrategy};
+use donaldswap::state::{CompletedGame, SwapState, SwapTrigger};
+use donaldswap::stats::{GameStats, SessionStats};
+
+fn config() -> AppConfig {
+    AppConfig {
+        games: vec![game("a.exe", "A"), game("b.exe", "B"), game("c.exe", "C")],
+        ..Default::default()
+    }
+}
+
+/// A session that played `games` back to back, 60 seconds each.
+fn played(games: &[&str]) -> SwapState {
+    SwapState {
+        history: games
+            .iter()
+            .enumerate()
+            .map(|(i, g)| stint(g, i as i64 * 60, i as i64 * 60 + 60, SwapTrigger::Automatic))
+            .collect(),
+        swap_count: games.len() as u64,
+        ..Default::default()
+    }
+}
+
+fn find<'a>(stats: &'a SessionStats, name: &str) -> &'a GameStats {
+    stats.games.iter().find(|g| g.game_name == name).unwrap()
+}
+
+#[test]
+fn per_game_stints_include_the_one_in_progress() {
+    let state = SwapState {
+        current_game: Some("A".to_string()),
+        current_exe: Some("a.exe".to_string()),
+        last_swap_at: Some(start() + secs(150)),
+        history: vec![
+            stint("A", 0, 100, SwapTrigger::Automatic),
+            stint("B", 100, 150, SwapTrigger::Forced),
+        ],
+        swap_count: 3,
+        ..Default::default()
+    };
+
+    let stats = SessionStats::new(&state, &config(), start() + secs(170));
+    assert_eq!(stats.total_seconds, 170);
+
+    let a = find(&stats, "A");
+    assert_eq!(a.swap_ins, 2);
+    assert_eq!(a.total_seconds, 120);
+    assert_eq!(a.average_stint_seconds, 60.0);
+    assert_eq!(a.shortest_stint_seconds, Some(20));
+    assert_eq!(a.longest_stint_seconds, Some(100));
+    assert!((a.share - 120.0 / 170.0).abs() < 1e-9);
+    assert_eq!(a.since_last_played_seconds, Some(0));
+    assert_eq!(a.longest_gap_seconds, Some(50));
+
+    let b = find(&stats, "B");
+    assert_eq!(b.since_last_played_seconds, Some(20));
+    assert_eq!(b.longest_gap_seconds, Some(20));
+
+    let c = find(&stats, "C");
+    assert_eq!(c.swap_ins, 0);
+    assert_eq!(c.shortest_stint_seconds, None);
+    assert_eq!(c.since_last_played_seconds, None);
+    assert_eq!(c.longest_gap_seconds, None);
+
+    let order: Vec<_> = stats.games.iter().map(|g| g.game_name.as_str()).collect();
+    assert_eq!(order, vec!["A", "B", "C"]);
+}
+
+#[test]
+fn fair_share_never_repeats_the_previous_game() {
+    // First pick is one of three, every later one is one of the other two
+    let stats = SessionStats::new(&played(&["A", "B", "A"]), &config(), start() + secs(180));
+    let expected = |name| find(&stats, name).expected_picks;
+    assert!((expected("A") - (1.0 / 3.0 + 0.5)).abs() < 1e-9);
+    assert!((expected("B") - (1.0 / 3.0 + 0.5)).abs() < 1e-9);
+    assert!((expected("C") - (1.0 / 3.0 + 1.0)).abs() < 1e-9);
+    assert_eq!(find(&stats, "A").random_picks, 2);
+    assert_eq!(stats.fairness.random_picks, 3);
+    assert_eq!(stats.fairness.degrees_of_freedom, 2);
+}
+
+#[test]
+fn forced_and_queued_swaps_are_not_random_picks() {
+    let mut state = played(&["A", "B"]);
+    state.history[1].trigger = SwapTrigger::Forced;
+    state
+        .history
+        .push(stint("C", 120, 180, SwapTrigger::Queued));
+
+    let stats = SessionStats::new(&state, &config(), start() + secs(180));
+    assert_eq!(stats.fairness.random_picks, 1);
+    assert_eq!(find(&stats, "B").swap_ins, 1);
+    assert_eq!(find(&stats, "B").random_picks, 0);
+}
+
+#[test]
+fn weights_and_completions_shape_the_fair_share() {
+    let mut config = config();
+    config.selection_strategy = SelectionStrategy::Weighted;
+    config.games[0].weight = 2.0;
+
+    let mut state = played(&["A", "B", "C"]);
+    state.completed.push(CompletedGame {
+        game_name: "A".to_string(),
+        exe_name: "a.exe".to_string(),
+        completed_at: start() + secs(60),
+        total_seconds: 60,
+    });
+
+    let stats = SessionStats::new(&state, &config, start() + secs(180));
+    let expected = |name| find(&stats, name).expected_picks;
+    // A: 2/4 of the first pick, then completed
+    assert!((expected("A") - 0.5).abs() < 1e-9);
+    // B: 1/4, then only B or C after A, then only C after B
+    assert!((expected("B") - (0.25 + 0.5)).abs() < 1e-9);
+    assert!((expected("C") - (0.25 + 0.5 + 1.0)).abs() < 1e-9);
+}
+
+#[test]
+fn p_value_flags_a_skewed_rotation() {
+    let even: Vec<&str> = ["A", "B", "C"].iter().copied().cycle().take(30).collect();
+    let stats = SessionStats::new(&played(&even), &config(), start());
+    assert!(stats.fairness.p_value.unwrap() > 0.5);
+
+    // C never comes up although it's a candidate every other pick
+    let skewed: Vec<&str> = ["A", "B"].iter().copied().cycle().take(30).collect();
+    let stats = SessionStats::new(&played(&skewed), &config(), start());
+    assert!(stats.fairness.p_value.unwrap() < 0.001);
+}
+
+#[test]
+fn p_value_needs_two_games() {
+    let config = AppConfig {
+        games: vec![game("a.exe", "A")],
+        ..Default::default()
+    };
+    let stats = SessionStats::new(&played(&["A"]), &config, start());
+    assert_eq!(stats.fairness.degrees_of_freedom, 0);
+    assert_eq!(stats.fairness.p_value, None);
+
+    let stats = SessionStats::new(&SwapState::default(), &AppConfig::default(), start());
+    assert!(stats.games.is_empty());
+    assert_eq!(stats.fairness.random_picks, 0);
+}
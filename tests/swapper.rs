@@ -0,0 +1,1450 @@
+mod common;
+
+use chrono::{TimeZone, Utc};
+use common::{game, harness, Harness};
+use donaldswap::clock::{Clock, TokioClock};
+use donaldswap::config::{AppConfig, KeyDelivery, PauseMethod, SelectionStrategy};
+use donaldswap::duration::SwapDuration;
+use donaldswap::input::KeyEvent;
+use donaldswap::keys::{Key, KeySequence};
+use donaldswap::session::SessionSnapshot;
+use donaldswap::state::{
+    CompletedGame, ScheduledSwap, SessionEvent, SwapFailure, SwapState, SwapTrigger,
+};
+use donaldswap::swapper::{calculate_delay, stint_bounds, InvalidTarget};
+use donaldswap::windows::{MockWindowConfig, WindowBackend};
+use std::time::Duration;
+
+fn config(min_swap_minutes: u64, max_swap_minutes: u64) -> AppConfig {
+    AppConfig {
+        games: vec![game("a.exe", "Game A"), game("b.exe", "Game B")],
+        min_swap: SwapDuration::from_minutes(min_swap_minutes),
+        max_swap: SwapDuration::from_minutes(max_swap_minutes),
+        ..Default::default()
+    }
+}
+
+fn minutes(minutes: u64) -> SwapDuration {
+    SwapDuration::from_minutes(minutes)
+}
+
+async fn advance(seconds: u64) {
+    tokio::time::sleep(Duration::from_secs(seconds)).await;
+}
+
+#[test]
+fn delay_is_fixed_when_min_equals_max() {
+    assert_eq!(
+        calculate_delay(&config(3, 3), None, &mut rand::thread_rng()),
+        180
+    );
+}
+
+#[test]
+fn delay_uses_max_when_min_exceeds_max() {
+    assert_eq!(
+        calculate_delay(&config(10, 2), None, &mut rand::thread_rng()),
+        120
+    );
+}
+
+#[test]
+fn delay_stays_within_bounds() {
+    let config = config(5, 15);
+    for _ in 0..1000 {
+        let delay = calculate_delay(&config, None, &mut rand::thread_rng());
+        assert!((300..=900).contains(&delay), "delay {} out of range", delay);
+    }
+}
+
+#[test]
+fn sub_minute_intervals_keep_second_precision() {
+    let config = AppConfig {
+        min_swap: SwapDuration::from_secs(20),
+        max_swap: "1m30s".parse().unwrap(),
+        ..Default::default()
+    };
+    for _ in 0..1000 {
+        let delay = calculate_delay(&config, None, &mut rand::thread_rng());
+        assert!((20..=90).contains(&delay), "delay {} out of range", delay);
+    }
+}
+
+#[test]
+fn zero_interval_waits_at_least_a_second() {
+    let config = AppConfig {
+        min_swap: SwapDuration::from_secs(0),
+        max_swap: SwapDuration::from_secs(0),
+        ..Default::default()
+    };
+    assert_eq!(calculate_delay(&config, None, &mut rand::thread_rng()), 1);
+}
+
+#[test]
+fn game_stint_limits_override_global_ones() {
+    let config = config(5, 15);
+    let mut roguelike = game("r.exe", "Roguelike");
+    roguelike.min_swap = Some(minutes(30));
+    roguelike.max_swap = Some(minutes(30));
+    assert_eq!(
+        calculate_delay(&config, Some(&roguelike), &mut rand::thread_rng()),
+        1800
+    );
+
+    // Games without overrides use the global range
+    let plain = game("p.exe", "Platformer");
+    assert_eq!(
+        stint_bounds(&config, Some(&plain)),
+        (minutes(5), minutes(15))
+    );
+}
+
+#[test]
+fn single_stint_override_stretches_the_other_bound() {
+    let config = config(5, 15);
+
+    let mut long = game("l.exe", "Long");
+    long.min_swap = Some(minutes(20));
+    assert_eq!(
+        stint_bounds(&config, Some(&long)),
+        (minutes(20), minutes(20))
+    );
+
+    let mut short = game("s.exe", "Short");
+    short.max_swap = Some(minutes(2));
+    assert_eq!(
+        stint_bounds(&config, Some(&short)),
+        (minutes(2), minutes(2))
+    );
+
+    let mut wide = game("w.exe", "Wide");
+    wide.max_swap = Some(minutes(60));
+    assert_eq!(
+        stint_bounds(&config, Some(&wide)),
+        (minutes(5), minutes(60))
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn tokio_clock_follows_paused_time() {
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let clock = TokioClock::starting_at(start);
+
+    assert_eq!(clock.now(), start);
+    advance(90).await;
+    assert_eq!(clock.now(), start + chrono::Duration::seconds(90));
+}
+
+#[tokio::test(start_paused = true)]
+async fn schedules_and_performs_first_swap() {
+    let h = harness(config(1, 1));
+    h.spawn().await;
+
+    advance(1).await;
+    let state = h.app_state.get_state().await;
+    assert_eq!(
+        state.next_swap_at,
+        Some(h.start + chrono::Duration::seconds(60))
+    );
+    assert_eq!(state.time_until_swap_seconds, Some(59));
+    assert_eq!(state.swap_count, 0);
+
+    advance(60).await;
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.swap_count, 1);
+    assert!(state.current_game.is_some());
+    // Two 100ms settle delays around the focus change
+    assert_eq!(state.last_swap_at, Some(h.at(60, 200)));
+    assert_eq!(state.next_swap_at, Some(h.at(120, 200)));
+}
+
+#[tokio::test(start_paused = true)]
+async fn no_swap_while_paused() {
+    let h = harness(config(1, 1));
+    h.spawn().await;
+    h.swapper.pause().await.unwrap();
+
+    advance(300).await;
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.swap_count, 0);
+    assert!(state.current_game.is_none());
+}
+
+#[tokio::test(start_paused = true)]
+async fn pause_freezes_countdown_until_resumed() {
+    let h = harness(config(1, 1));
+    h.spawn().await;
+
+    advance(30).await;
+    h.swapper.pause().await.unwrap();
+
+    advance(60).await;
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.swap_count, 0);
+    assert_eq!(state.time_until_swap_seconds, Some(30));
+
+    h.swapper.resume().await.unwrap();
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.next_swap_at, Some(h.at(120, 0)));
+
+    advance(29).await;
+    assert_eq!(h.app_state.get_state().await.swap_count, 0);
+
+    advance(2).await;
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.swap_count, 1);
+    assert_eq!(state.last_swap_at, Some(h.at(120, 200)));
+}
+
+#[tokio::test(start_paused = true)]
+async fn paused_time_is_excluded_from_stint() {
+    let h = harness(config(1, 1));
+    h.spawn().await;
+
+    // First swap lands at 60.2s, the second is due 60s later
+    advance(70).await;
+    h.swapper.pause().await.unwrap();
+
+    advance(45).await;
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.time_since_swap_seconds, Some(9));
+    assert_eq!(state.time_until_swap_seconds, Some(50));
+
+    h.swapper.resume().await.unwrap();
+    advance(60).await;
+
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.swap_count, 2);
+    assert_eq!(state.history[0].duration_seconds, 60);
+    assert_eq!(state.history[0].paused_seconds, 45);
+    assert_eq!(state.history[0].started_at, h.at(60, 200));
+    assert_eq!(state.history[0].ended_at, h.at(165, 400));
+    assert_eq!(state.total_times.values().sum::<u64>(), 60);
+}
+
+#[tokio::test(start_paused = true)]
+async fn history_records_each_stint() {
+    let h = harness(config(1, 1));
+    h.spawn().await;
+
+    advance(60 * 3 + 30).await;
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.swap_count, 3);
+
+    assert_eq!(state.history.len(), 2);
+    assert!(state.history.iter().all(|item| item.duration_seconds == 60));
+    // With two games the rotation alternates, so the latest stint was not the current game
+    assert_ne!(
+        Some(&state.history[1].game_name),
+        state.current_game.as_ref()
+    );
+    assert_ne!(state.history[0].game_name, state.history[1].game_name);
+
+    assert_eq!(state.total_times.get("Game A"), Some(&60));
+    assert_eq!(state.total_times.get("Game B"), Some(&60));
+}
+
+#[tokio::test(start_paused = true)]
+async fn history_keeps_every_stint() {
+    let h = harness(config(1, 1));
+    h.spawn().await;
+
+    advance(60 * 15 + 30).await;
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.swap_count, 15);
+    assert_eq!(state.history.len(), 14);
+    assert_eq!(state.total_times.values().sum::<u64>(), 14 * 60);
+
+    // Stints are back to back, oldest first
+    for pair in state.history.windows(2) {
+        assert_eq!(pair[0].ended_at, pair[1].started_at);
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn clients_only_get_the_latest_stints() {
+    let h = harness(config(1, 1));
+    h.spawn().await;
+
+    advance(60 * 15 + 30).await;
+    let state = h.app_state.get_state().await;
+    let sent = state.for_clients();
+    assert_eq!(sent.history, state.history[4..]);
+    assert_eq!(sent.history_len, 14);
+}
+
+#[tokio::test(start_paused = true)]
+async fn history_records_how_each_stint_started() {
+    let h = harness(config(1, 1));
+    h.spawn().await;
+
+    // Automatic first swap, then forced ones away and back
+    advance(70).await;
+    let picked = h.app_state.get_state().await.current_exe.unwrap();
+    let other = if picked == "a.exe" { "b.exe" } else { "a.exe" };
+    h.swapper.swap_to(other).await.unwrap();
+    h.swapper.swap_to(&picked).await.unwrap();
+
+    let state = h.app_state.get_state().await;
+    let first = state
+        .history
+        .iter()
+        .find(|item| item.started_at == h.at(60, 200))
+        .unwrap();
+    assert_eq!(first.trigger, SwapTrigger::Automatic);
+    assert_eq!(first.ended_at, h.at(70, 200));
+    assert!(!first.exe_name.is_empty());
+    assert_eq!(state.history.last().unwrap().trigger, SwapTrigger::Forced);
+    assert_eq!(state.history.last().unwrap().exe_name, other);
+}
+
+#[tokio::test(start_paused = true)]
+async fn swapping_to_the_current_game_is_rejected() {
+    let h = harness(config(5, 5));
+    h.spawn().await;
+    h.swapper.swap_to("a.exe").await.unwrap();
+
+    let err = h.swapper.swap_to("a.exe").await.unwrap_err();
+    assert!(err.is::<InvalidTarget>());
+    assert_eq!(err.to_string(), "Game A is already the current game");
+    let err = h.swapper.swap_to("nope.exe").await.unwrap_err();
+    assert!(err.is::<InvalidTarget>());
+
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.swap_count, 1);
+    assert_eq!(state.history.len(), 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn forced_swap_is_timestamped_by_the_clock() {
+    let h = harness(config(5, 5));
+    h.spawn().await;
+
+    advance(42).await;
+    h.swapper.force_swap().await.unwrap();
+
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.swap_count, 1);
+    assert_eq!(state.last_swap_at, Some(h.at(42, 200)));
+}
+
+#[tokio::test(start_paused = true)]
+async fn forced_swap_restarts_the_timer() {
+    let h = harness(config(1, 1));
+    h.spawn().await;
+
+    advance(50).await;
+    h.swapper.force_swap().await.unwrap();
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.next_swap_at, Some(h.at(110, 200)));
+
+    // The timer rolled before the forced swap must not fire
+    advance(30).await;
+    assert_eq!(h.app_state.get_state().await.swap_count, 1);
+
+    advance(31).await;
+    assert_eq!(h.app_state.get_state().await.swap_count, 2);
+}
+
+fn keys(sequence: &str) -> KeySequence {
+    sequence.parse().unwrap()
+}
+
+#[tokio::test(start_paused = true)]
+async fn swaps_send_the_leave_then_enter_keys() {
+    let mut config = config(5, 5);
+    config.games[0].on_leave = keys("Escape");
+    config.games[1].on_enter = keys("Ctrl+P, wait 200ms, Enter");
+    let h = harness(config);
+    h.spawn().await;
+
+    // Nothing is playing yet, and Game A has no enter keys
+    h.swapper.swap_to("a.exe").await.unwrap();
+    assert_eq!(h.input.take(), vec![]);
+
+    h.swapper.swap_to("b.exe").await.unwrap();
+    assert_eq!(
+        h.input.take(),
+        vec![
+            KeyEvent::Down(Key::Escape),
+            KeyEvent::Up(Key::Escape),
+            KeyEvent::Down(Key::Ctrl),
+            KeyEvent::Down(Key::Char('p')),
+            KeyEvent::Up(Key::Char('p')),
+            KeyEvent::Up(Key::Ctrl),
+            KeyEvent::Down(Key::Enter),
+            KeyEvent::Up(Key::Enter),
+        ]
+    );
+
+    // Game B has no leave keys
+    h.swapper.swap_to("a.exe").await.unwrap();
+    assert_eq!(h.input.take(), vec![]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn window_delivery_posts_keys_to_the_game() {
+    let mut config = config(5, 5);
+    config.games[0].on_leave = keys("Escape");
+    config.games[0].key_delivery = KeyDelivery::Window;
+    config.games[1].on_enter = keys("Enter");
+    let h = harness(config);
+    h.spawn().await;
+    let a = h.windows.find_window_by_exe("a.exe").unwrap();
+
+    h.swapper.swap_to("a.exe").await.unwrap();
+    h.swapper.swap_to("b.exe").await.unwrap();
+    assert_eq!(
+        h.input.take_posted(),
+        vec![
+            (a, KeyEvent::Down(Key::Escape)),
+            (a, KeyEvent::Up(Key::Escape))
+        ]
+    );
+    // Game B still gets its keys the usual way
+    assert_eq!(
+        h.input.take(),
+        vec![KeyEvent::Down(Key::Enter), KeyEvent::Up(Key::Enter)]
+    );
+
+    // With its window gone, Game A's keys go to whatever has focus
+    h.swapper.swap_to("a.exe").await.unwrap();
+    h.input.take();
+    h.windows.remove("a.exe");
+    h.swapper.swap_to("b.exe").await.unwrap();
+    assert_eq!(h.input.take_posted(), vec![]);
+    assert_eq!(
+        h.input.take(),
+        vec![
+            KeyEvent::Down(Key::Escape),
+            KeyEvent::Up(Key::Escape),
+            KeyEvent::Down(Key::Enter),
+            KeyEvent::Up(Key::Enter),
+        ]
+    );
+}
+
+fn pid(h: &Harness, exe_name: &str) -> u32 {
+    let windows = h.windows.enumerate_windows().unwrap();
+    windows.iter().find(|w| w.exe_name == exe_name).unwrap().pid
+}
+
+#[tokio::test(start_paused = true)]
+async fn keys_the_window_rejects_are_sent_globally() {
+    let mut config = config(5, 5);
+    config.games[0].on_leave = keys("Escape");
+    config.games[0].key_delivery = KeyDelivery::Window;
+    let h = harness(config);
+    h.spawn().await;
+
+    h.swapper.swap_to("a.exe").await.unwrap();
+    h.input.set_reject_posted(true);
+    h.swapper.swap_to("b.exe").await.unwrap();
+    assert_eq!(h.input.take_posted(), vec![]);
+    assert_eq!(
+        h.input.take(),
+        vec![KeyEvent::Down(Key::Escape), KeyEvent::Up(Key::Escape)]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn suspended_games_are_paused_without_keys() {
+    let mut config = config(5, 5);
+    for game in &mut config.games {
+        game.on_leave = keys("Escape");
+        game.on_enter = keys("Enter");
+    }
+    config.games[0].pause_method = PauseMethod::Suspend;
+    let h = harness(config);
+    h.spawn().await;
+    let a = pid(&h, "a.exe");
+
+    h.swapper.swap_to("a.exe").await.unwrap();
+    h.swapper.swap_to("b.exe").await.unwrap();
+    assert_eq!(h.processes.suspended(), vec![a]);
+    // Only Game B's enter keys
+    assert_eq!(
+        h.input.take(),
+        vec![KeyEvent::Down(Key::Enter), KeyEvent::Up(Key::Enter)]
+    );
+
+    h.swapper.swap_to("a.exe").await.unwrap();
+    assert!(h.processes.suspended().is_empty());
+    assert_eq!(
+        h.input.take(),
+        vec![KeyEvent::Down(Key::Escape), KeyEvent::Up(Key::Escape)]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn suspending_refuses_obs_and_ourselves() {
+    let mut config = config(5, 5);
+    config.games.push(game("obs64.exe", "OBS"));
+    config.games.push(game("donaldswap.exe", "DonaldSwap"));
+    for game in &mut config.games {
+        game.pause_method = PauseMethod::Suspend;
+    }
+    let h = harness(config);
+    h.windows.remove("donaldswap.exe");
+    h.windows.add(MockWindowConfig {
+        exe_name: "donaldswap.exe".to_string(),
+        title: None,
+        focus_fails: false,
+        focus_ignored: false,
+        pid: Some(std::process::id()),
+    });
+    h.spawn().await;
+
+    for exe in ["obs64.exe", "donaldswap.exe", "a.exe"] {
+        h.swapper.swap_to(exe).await.unwrap();
+    }
+    assert!(h.processes.suspended().is_empty());
+
+    h.swapper.swap_to("b.exe").await.unwrap();
+    assert_eq!(h.processes.suspended(), vec![pid(&h, "a.exe")]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn suspended_games_are_resumed_when_no_longer_swapped_out() {
+    let mut config = config(5, 5);
+    config.games.push(game("c.exe", "Game C"));
+    for game in &mut config.games {
+        game.pause_method = PauseMethod::Suspend;
+    }
+    let h = harness(config);
+    h.spawn().await;
+    let (a, b) = (pid(&h, "a.exe"), pid(&h, "b.exe"));
+
+    for exe in ["a.exe", "b.exe", "c.exe"] {
+        h.swapper.swap_to(exe).await.unwrap();
+    }
+    assert_eq!(h.processes.suspended(), vec![a, b]);
+
+    // Switching a game back to keys lets it run again
+    h.update_config(|c| c.games[0].pause_method = PauseMethod::Keys)
+        .await;
+    assert_eq!(h.processes.suspended(), vec![b]);
+
+    h.swapper.complete("b.exe").await.unwrap();
+    assert!(h.processes.suspended().is_empty());
+
+    // A failed swap leaves Game C running and Game A paused
+    h.update_config(|c| c.games[0].pause_method = PauseMethod::Suspend)
+        .await;
+    h.windows.set_focus_fails("a.exe", true);
+    assert!(h.swapper.swap_to("a.exe").await.is_err());
+    assert_eq!(h.processes.suspended(), vec![a]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn suspended_games_stay_paused_into_a_new_session() {
+    let mut config = config(5, 5);
+    for game in &mut config.games {
+        game.pause_method = PauseMethod::Suspend;
+    }
+    let h = harness(config);
+    h.spawn().await;
+    let a = pid(&h, "a.exe");
+
+    h.swapper.swap_to("a.exe").await.unwrap();
+    h.swapper.swap_to("b.exe").await.unwrap();
+    h.swapper.new_session(2, "Day 2").await.unwrap();
+
+    // Game B carries on, so Game A is still swapped out
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.current_exe.as_deref(), Some("b.exe"));
+    assert_eq!(h.processes.suspended(), vec![a]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn failed_swaps_unpause_the_game_that_was_playing() {
+    let mut config = config(5, 5);
+    config.games[0].on_leave = keys("Escape");
+    config.games[0].on_enter = keys("Enter");
+    config.games[1].on_enter = keys("Tab");
+    let h = harness(config);
+    h.spawn().await;
+
+    h.swapper.swap_to("a.exe").await.unwrap();
+    h.input.take();
+
+    // Something else grabbed focus, and Game B can't be brought to the front
+    let chat = h.windows.add(MockWindowConfig {
+        exe_name: "chat.exe".to_string(),
+        title: None,
+        focus_fails: false,
+        focus_ignored: false,
+        pid: None,
+    });
+    h.windows.focus_window(chat).unwrap();
+    h.windows.set_focus_fails("b.exe", true);
+    assert!(h.swapper.swap_to("b.exe").await.is_err());
+
+    // Game A was paused on the way out, so it's focused and unpaused again
+    assert_eq!(
+        h.input.take(),
+        vec![
+            KeyEvent::Down(Key::Escape),
+            KeyEvent::Up(Key::Escape),
+            KeyEvent::Down(Key::Enter),
+            KeyEvent::Up(Key::Enter),
+        ]
+    );
+    let foreground = h.windows.foreground_window().unwrap().unwrap();
+    assert_eq!(foreground.exe_name, "a.exe");
+}
+
+#[tokio::test(start_paused = true)]
+async fn swap_is_given_up_when_focus_lands_elsewhere() {
+    let mut config = config(5, 5);
+    config.games[1].on_enter = keys("Escape");
+    config.focus_timeout_ms = 1000;
+    let h = harness(config);
+    h.spawn().await;
+    h.swapper.swap_to("a.exe").await.unwrap();
+    let mut events = h.app_state.broadcaster.subscribe_events();
+
+    // Focusing "works", but Game A stays in front
+    h.windows.set_focus_ignored("b.exe", true);
+    let err = h.swapper.swap_to("b.exe").await.unwrap_err().to_string();
+    assert!(err.contains("a.exe has focus"), "{}", err);
+    assert_eq!(h.input.take(), vec![]);
+
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.current_exe.as_deref(), Some("a.exe"));
+    assert_eq!(state.swap_count, 1);
+    assert_eq!(
+        events.try_recv().unwrap(),
+        SessionEvent::SwapFailed(SwapFailure {
+            game_name: "Game B".to_string(),
+            exe_name: "b.exe".to_string(),
+            reason: err,
+            failed_at: h.at(1, 300),
+        })
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn another_window_of_the_same_exe_does_not_count_as_focused() {
+    let mut config = config(5, 5);
+    config.games[1].on_enter = keys("Escape");
+    config.focus_timeout_ms = 1000;
+    let h = harness(config);
+    h.spawn().await;
+    h.swapper.swap_to("a.exe").await.unwrap();
+
+    // Game B's launcher is in front, but Game B itself never comes up
+    h.windows.set_focus_ignored("b.exe", true);
+    let launcher = h.windows.add(MockWindowConfig {
+        exe_name: "b.exe".to_string(),
+        title: Some("Game B Launcher".to_string()),
+        focus_fails: false,
+        focus_ignored: false,
+        pid: None,
+    });
+    h.windows.focus_window(launcher).unwrap();
+
+    let err = h.swapper.swap_to("b.exe").await.unwrap_err().to_string();
+    assert!(err.contains("b.exe has focus"), "{}", err);
+    assert_eq!(h.input.take(), vec![]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn refused_focus_is_retried_until_the_timeout() {
+    let mut config = config(5, 5);
+    config.games[1].on_enter = keys("Escape");
+    let h = harness(config);
+    h.spawn().await;
+    h.swapper.swap_to("a.exe").await.unwrap();
+
+    // Focus requests error out for a while, well within the 2s timeout
+    h.windows.set_focus_fails("b.exe", true);
+    let windows = h.windows.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        windows.set_focus_fails("b.exe", false);
+    });
+    h.swapper.swap_to("b.exe").await.unwrap();
+    assert_eq!(
+        h.input.take(),
+        vec![KeyEvent::Down(Key::Escape), KeyEvent::Up(Key::Escape)]
+    );
+
+    // Once the timeout runs out the swap is given up
+    h.windows.set_focus_fails("a.exe", true);
+    let before = h.app_state.clock.now();
+    let err = h.swapper.swap_to("a.exe").await.unwrap_err().to_string();
+    assert!(err.contains("b.exe has focus"), "{}", err);
+    assert!(h.app_state.clock.now() - before >= chrono::Duration::seconds(2));
+}
+
+#[tokio::test(start_paused = true)]
+async fn enter_keys_wait_until_the_game_is_in_front() {
+    let mut config = config(5, 5);
+    config.games[1].on_enter = keys("Escape");
+    config.focus_settle_ms = 250;
+    let h = harness(config);
+    h.spawn().await;
+    h.swapper.swap_to("a.exe").await.unwrap();
+
+    // The window manager only honours the focus request after a while
+    h.windows.set_focus_ignored("b.exe", true);
+    let windows = h.windows.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        windows.set_focus_ignored("b.exe", false);
+    });
+    h.swapper.swap_to("b.exe").await.unwrap();
+
+    assert_eq!(
+        h.input.take(),
+        vec![KeyEvent::Down(Key::Escape), KeyEvent::Up(Key::Escape)]
+    );
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.current_exe.as_deref(), Some("b.exe"));
+    // The swap started at 350ms; focus is asked for again at 800ms and 900ms,
+    // and only the second request comes after the window manager gave in
+    assert_eq!(state.last_swap_at, Some(h.at(0, 1000)));
+}
+
+#[tokio::test(start_paused = true)]
+async fn timing_change_reschedules_immediately() {
+    let h = harness(config(10, 10));
+    h.spawn().await;
+
+    advance(30).await;
+    h.update_config(|c| {
+        c.min_swap = minutes(1);
+        c.max_swap = minutes(1);
+    })
+    .await;
+
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.next_swap_at, Some(h.at(90, 0)));
+
+    advance(61).await;
+    assert_eq!(h.app_state.get_state().await.swap_count, 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn unrelated_config_change_keeps_the_timer() {
+    let h = harness(config(1, 1));
+    h.spawn().await;
+
+    advance(30).await;
+    h.update_config(|c| c.hide_next_swap = true).await;
+
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.next_swap_at, Some(h.at(60, 0)));
+}
+
+#[tokio::test(start_paused = true)]
+async fn disabling_auto_swap_cancels_the_timer() {
+    let h = harness(config(1, 1));
+    h.spawn().await;
+
+    advance(30).await;
+    h.update_config(|c| c.auto_swap_enabled = false).await;
+    assert_eq!(h.app_state.get_state().await.next_swap_at, None);
+
+    advance(300).await;
+    assert_eq!(h.app_state.get_state().await.swap_count, 0);
+
+    h.update_config(|c| c.auto_swap_enabled = true).await;
+    assert_eq!(
+        h.app_state.get_state().await.next_swap_at,
+        Some(h.at(390, 0))
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn reschedule_rolls_a_fresh_delay() {
+    let h = harness(config(1, 1));
+    h.spawn().await;
+
+    advance(45).await;
+    h.swapper.reschedule().await.unwrap();
+    assert_eq!(
+        h.app_state.get_state().await.next_swap_at,
+        Some(h.at(105, 0))
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn adjusting_the_timer_moves_the_next_swap() {
+    let h = harness(config(1, 1));
+    h.spawn().await;
+
+    advance(10).await;
+    h.swapper.adjust_timer(300).await.unwrap();
+    assert_eq!(
+        h.app_state.get_state().await.next_swap_at,
+        Some(h.at(360, 0))
+    );
+
+    h.swapper.adjust_timer(-240).await.unwrap();
+    assert_eq!(
+        h.app_state.get_state().await.next_swap_at,
+        Some(h.at(120, 0))
+    );
+
+    advance(111).await;
+    assert_eq!(h.app_state.get_state().await.swap_count, 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn shortening_past_now_swaps_immediately() {
+    let h = harness(config(5, 5));
+    h.spawn().await;
+
+    advance(10).await;
+    h.swapper.adjust_timer(-3600).await.unwrap();
+    advance(1).await;
+
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.swap_count, 1);
+    assert_eq!(state.last_swap_at, Some(h.at(10, 200)));
+}
+
+#[tokio::test(start_paused = true)]
+async fn setting_the_timer_while_paused_keeps_it_frozen() {
+    let h = harness(config(5, 5));
+    h.spawn().await;
+
+    advance(10).await;
+    h.swapper.pause().await.unwrap();
+    advance(20).await;
+    h.swapper.set_timer(30).await.unwrap();
+
+    advance(100).await;
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.time_until_swap_seconds, Some(30));
+    assert_eq!(state.swap_count, 0);
+
+    h.swapper.resume().await.unwrap();
+    advance(31).await;
+    assert_eq!(h.app_state.get_state().await.swap_count, 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn timer_changes_are_broadcast() {
+    let h = harness(config(1, 1));
+    h.spawn().await;
+
+    let mut updates = h.app_state.broadcaster.subscribe();
+    h.swapper.set_timer(5).await.unwrap();
+
+    let state = updates.recv().await.unwrap();
+    assert_eq!(state.next_swap_at, Some(h.at(5, 0)));
+}
+
+#[tokio::test(start_paused = true)]
+async fn timer_changes_need_a_scheduled_swap() {
+    let mut config = config(1, 1);
+    config.auto_swap_enabled = false;
+    let h = harness(config);
+    h.spawn().await;
+
+    assert!(h.swapper.adjust_timer(60).await.is_err());
+    assert!(h.swapper.set_timer(60).await.is_err());
+}
+
+#[tokio::test(start_paused = true)]
+async fn forced_swap_goes_to_the_requested_game() {
+    let h = harness(config(5, 5));
+    h.spawn().await;
+
+    h.swapper.swap_to("b.exe").await.unwrap();
+    assert_eq!(
+        h.app_state.get_state().await.current_exe.as_deref(),
+        Some("b.exe")
+    );
+
+    h.swapper.swap_to("A.EXE").await.unwrap();
+    assert_eq!(
+        h.app_state.get_state().await.current_exe.as_deref(),
+        Some("a.exe")
+    );
+
+    assert!(h.swapper.swap_to("missing.exe").await.is_err());
+}
+
+#[tokio::test(start_paused = true)]
+async fn queued_game_is_picked_next_then_cleared() {
+    let mut config = config(1, 1);
+    config.games.push(game("c.exe", "Game C"));
+    let h = harness(config);
+    h.spawn().await;
+
+    h.swapper.swap_to("a.exe").await.unwrap();
+    h.swapper.queue_next(Some("c.exe")).await.unwrap();
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.queued_game.as_deref(), Some("Game C"));
+
+    advance(61).await;
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.current_exe.as_deref(), Some("c.exe"));
+    assert_eq!(state.stint_trigger, SwapTrigger::Queued);
+    assert_eq!(state.queued_game, None);
+    assert_eq!(state.queued_exe, None);
+}
+
+#[tokio::test(start_paused = true)]
+async fn queueing_rejects_unknown_and_current_games() {
+    let h = harness(config(1, 1));
+    h.spawn().await;
+
+    h.swapper.swap_to("a.exe").await.unwrap();
+    assert!(h.swapper.queue_next(Some("a.exe")).await.is_err());
+    assert!(h.swapper.queue_next(Some("nope.exe")).await.is_err());
+
+    h.swapper.queue_next(Some("b.exe")).await.unwrap();
+    h.swapper.queue_next(None).await.unwrap();
+    assert_eq!(h.app_state.get_state().await.queued_exe, None);
+}
+
+#[tokio::test(start_paused = true)]
+async fn swapped_in_game_uses_its_own_stint_length() {
+    let mut config = config(1, 1);
+    config.games[1].min_swap = Some(minutes(10));
+    config.games[1].max_swap = Some(minutes(10));
+    let h = harness(config);
+    h.spawn().await;
+
+    h.swapper.swap_to("b.exe").await.unwrap();
+    assert_eq!(
+        h.app_state.get_state().await.next_swap_at,
+        Some(h.at(600, 200))
+    );
+
+    h.swapper.swap_to("a.exe").await.unwrap();
+    assert_eq!(
+        h.app_state.get_state().await.next_swap_at,
+        Some(h.at(60, 400))
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn editing_the_current_games_stint_length_reschedules() {
+    let h = harness(config(1, 1));
+    h.spawn().await;
+    h.swapper.swap_to("a.exe").await.unwrap();
+
+    advance(10).await;
+    h.update_config(|c| c.games[0].min_swap = Some(minutes(5)))
+        .await;
+    assert_eq!(
+        h.app_state.get_state().await.next_swap_at,
+        Some(h.at(310, 200))
+    );
+
+    // Other games' limits don't touch the running timer
+    h.update_config(|c| c.games[1].min_swap = Some(minutes(3)))
+        .await;
+    assert_eq!(
+        h.app_state.get_state().await.next_swap_at,
+        Some(h.at(310, 200))
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn time_budget_shortens_the_final_stint() {
+    let mut config = config(10, 10);
+    config.games[0].time_budget = Some(minutes(2));
+    let h = harness(config);
+    h.spawn().await;
+
+    h.swapper.swap_to("a.exe").await.unwrap();
+    assert_eq!(
+        h.app_state.get_state().await.next_swap_at,
+        Some(h.at(120, 200))
+    );
+
+    // Adding time can't push the game past its budget either
+    h.swapper.adjust_timer(300).await.unwrap();
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.next_swap_at, Some(h.at(120, 200)));
+    assert_eq!(state.budget_remaining("a.exe", h.at(60, 200)), Some(60));
+
+    advance(121).await;
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.current_exe.as_deref(), Some("b.exe"));
+    assert_eq!(state.total_times.get("Game A"), Some(&120));
+    assert_eq!(state.played_times.get("a.exe"), Some(&120));
+    assert!(state.budget_used_up("a.exe", h.at(121, 0)));
+}
+
+#[tokio::test(start_paused = true)]
+async fn used_up_game_leaves_the_rotation() {
+    let mut config = config(1, 1);
+    config.games[0].time_budget = Some(minutes(1));
+    let h = harness(config);
+    h.spawn().await;
+
+    h.swapper.swap_to("a.exe").await.unwrap();
+    advance(60 * 3 + 1).await;
+
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.swap_count, 4);
+    assert_eq!(state.current_exe.as_deref(), Some("b.exe"));
+    assert_eq!(state.total_times.get("Game A"), Some(&60));
+
+    assert!(h.swapper.swap_to("a.exe").await.is_err());
+    assert!(h.swapper.queue_next(Some("a.exe")).await.is_err());
+}
+
+#[tokio::test(start_paused = true)]
+async fn budgets_follow_the_game_not_its_name() {
+    let mut config = config(1, 1);
+    config.games[1].display_name = "Game A".to_string();
+    config.games[0].time_budget = Some(minutes(1));
+    let h = harness(config);
+    h.spawn().await;
+
+    h.swapper.swap_to("a.exe").await.unwrap();
+    advance(61).await;
+
+    // Only the budgeted game is out, even though both go by "Game A"
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.current_exe.as_deref(), Some("b.exe"));
+    assert!(state.budget_used_up("a.exe", h.at(61, 0)));
+    assert_eq!(state.budget_remaining("b.exe", h.at(61, 0)), None);
+
+    // Renaming the game keeps what it has played against its budget
+    h.update_config(|c| c.games[0].display_name = "Renamed".to_string())
+        .await;
+    assert!(h.swapper.swap_to("a.exe").await.is_err());
+}
+
+#[tokio::test(start_paused = true)]
+async fn session_ends_when_every_budget_is_used_up() {
+    let mut config = config(5, 5);
+    config.games[0].time_budget = Some(minutes(1));
+    config.games[1].time_budget = Some(minutes(1));
+    let h = harness(config);
+    h.spawn().await;
+
+    h.swapper.swap_to("a.exe").await.unwrap();
+    advance(121).await;
+
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.swap_count, 2);
+    assert_eq!(state.current_game, None);
+    assert_eq!(state.next_swap_at, None);
+    assert_eq!(state.session_ended_at, Some(h.at(120, 400)));
+    assert_eq!(state.total_times.get("Game A"), Some(&60));
+    assert_eq!(state.total_times.get("Game B"), Some(&60));
+
+    advance(600).await;
+    assert_eq!(h.app_state.get_state().await.swap_count, 2);
+    assert!(h.swapper.force_swap().await.is_err());
+}
+
+#[tokio::test(start_paused = true)]
+async fn raising_the_current_games_budget_reschedules() {
+    let mut config = config(10, 10);
+    config.games[0].time_budget = Some(minutes(2));
+    let h = harness(config);
+    h.spawn().await;
+    h.swapper.swap_to("a.exe").await.unwrap();
+
+    h.update_config(|c| c.games[0].time_budget = Some(minutes(5)))
+        .await;
+    assert_eq!(
+        h.app_state.get_state().await.next_swap_at,
+        Some(h.at(300, 200))
+    );
+    assert_eq!(
+        h.app_state.get_state().await.time_budgets.get("a.exe"),
+        Some(&300)
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn completing_the_active_game_swaps_away() {
+    let h = harness(config(5, 5));
+    h.spawn().await;
+    let mut events = h.app_state.broadcaster.subscribe_events();
+
+    h.swapper.swap_to("a.exe").await.unwrap();
+    advance(30).await;
+    h.swapper.complete("a.exe").await.unwrap();
+
+    let completed = CompletedGame {
+        game_name: "Game A".to_string(),
+        exe_name: "a.exe".to_string(),
+        completed_at: h.at(30, 200),
+        total_seconds: 30,
+    };
+    assert_eq!(
+        events.try_recv().unwrap(),
+        SessionEvent::GameCompleted(completed.clone())
+    );
+
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.completed, vec![completed]);
+    assert_eq!(state.current_exe.as_deref(), Some("b.exe"));
+    assert_eq!(state.total_times.get("Game A"), Some(&30));
+    assert_eq!(state.next_swap_at, Some(h.at(330, 400)));
+
+    assert!(h.swapper.complete("a.exe").await.is_err());
+    assert!(h.swapper.swap_to("a.exe").await.is_err());
+    assert!(h.swapper.queue_next(Some("a.exe")).await.is_err());
+}
+
+#[tokio::test(start_paused = true)]
+async fn completed_game_is_never_picked() {
+    let mut config = config(1, 1);
+    config.games.push(game("c.exe", "Game C"));
+    let h = harness(config);
+    h.spawn().await;
+
+    h.swapper.complete("c.exe").await.unwrap();
+    advance(60 * 10 + 30).await;
+
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.swap_count, 10);
+    assert_eq!(state.total_times.get("Game C"), None);
+    assert_eq!(state.completed[0].total_seconds, 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn completing_the_last_game_ends_the_session() {
+    let h = harness(config(5, 5));
+    h.spawn().await;
+
+    h.swapper.swap_to("a.exe").await.unwrap();
+    h.swapper.complete("b.exe").await.unwrap();
+    assert_eq!(
+        h.app_state.get_state().await.current_exe.as_deref(),
+        Some("a.exe")
+    );
+
+    advance(10).await;
+    h.swapper.complete("a.exe").await.unwrap();
+
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.current_game, None);
+    assert_eq!(state.next_swap_at, None);
+    assert_eq!(state.session_ended_at, Some(h.at(10, 200)));
+    assert_eq!(state.total_times.get("Game A"), Some(&10));
+    assert_eq!(state.completed.len(), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn resumed_session_keeps_its_countdown() {
+    let h = harness(config(5, 5));
+    let snapshot = SessionSnapshot {
+        saved_at: h.at(0, 0),
+        state: SwapState {
+            current_game: Some("Game A".to_string()),
+            current_exe: Some("a.exe".to_string()),
+            last_swap_at: Some(h.start - chrono::Duration::seconds(200)),
+            next_swap_at: Some(h.at(40, 0)),
+            swap_count: 7,
+            ..Default::default()
+        },
+    };
+    let state = snapshot.into_state(h.start);
+    h.app_state.update_state(|s| *s = state).await;
+    h.spawn().await;
+
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.next_swap_at, Some(h.at(40, 0)));
+    assert_eq!(state.time_since_swap_seconds, Some(200));
+
+    advance(41).await;
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.swap_count, 8);
+    assert_eq!(state.current_exe.as_deref(), Some("b.exe"));
+    assert_eq!(state.history.last().unwrap().duration_seconds, 240);
+}
+
+#[tokio::test(start_paused = true)]
+async fn restoring_replaces_the_running_session() {
+    let h = harness(config(5, 5));
+    h.spawn().await;
+    h.swapper.swap_to("a.exe").await.unwrap();
+
+    advance(100).await;
+    let snapshot = SessionSnapshot {
+        saved_at: h.at(0, 0),
+        state: SwapState {
+            current_game: Some("Game B".to_string()),
+            current_exe: Some("b.exe".to_string()),
+            last_swap_at: Some(h.start - chrono::Duration::seconds(30)),
+            next_swap_at: Some(h.at(60, 0)),
+            swap_count: 12,
+            ..Default::default()
+        },
+    };
+    h.swapper.restore(snapshot).await.unwrap();
+
+    // The 100s between the snapshot and the restore count as downtime
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.swap_count, 12);
+    assert_eq!(state.current_exe.as_deref(), Some("b.exe"));
+    assert_eq!(state.time_since_swap_seconds, Some(30));
+    assert_eq!(state.next_swap_at, Some(h.at(160, 200)));
+}
+
+fn seeded(seed: Option<u64>) -> AppConfig {
+    let mut config = config(1, 10);
+    config.games.push(game("c.exe", "Game C"));
+    config.seed = seed;
+    config
+}
+
+async fn schedule_of(h: &Harness) -> Vec<(String, u64)> {
+    h.app_state
+        .get_state()
+        .await
+        .history
+        .into_iter()
+        .map(|item| (item.game_name, item.duration_seconds))
+        .collect()
+}
+
+#[tokio::test(start_paused = true)]
+async fn same_seed_gives_the_same_schedule() {
+    let first = harness(seeded(Some(42)));
+    let second = harness(seeded(Some(42)));
+    first.spawn().await;
+    second.spawn().await;
+
+    advance(60 * 60).await;
+    let schedule = schedule_of(&first).await;
+    assert!(schedule.len() > 5);
+    assert_eq!(schedule, schedule_of(&second).await);
+    assert_eq!(first.app_state.get_state().await.seed, Some(42));
+}
+
+#[tokio::test(start_paused = true)]
+async fn unseeded_sessions_publish_a_random_seed() {
+    let h = harness(seeded(None));
+    h.spawn().await;
+
+    let seed = h.app_state.get_state().await.seed.unwrap();
+    h.update_config(|c| c.summary_template = "{game}".to_string())
+        .await;
+    assert_eq!(h.app_state.get_state().await.seed, Some(seed));
+
+    h.update_config(|c| c.seed = Some(7)).await;
+    assert_eq!(h.app_state.get_state().await.seed, Some(7));
+}
+
+#[tokio::test(start_paused = true)]
+async fn restored_session_continues_its_draws() {
+    let h = harness(seeded(Some(9)));
+    h.spawn().await;
+    advance(60 * 30).await;
+    let snapshot = SessionSnapshot {
+        saved_at: h.at(60 * 30, 0),
+        state: h.app_state.get_state().await,
+    };
+    advance(60 * 30).await;
+    let expected = schedule_of(&h).await;
+
+    // The restored swapper's clock starts over at `start`, so only the stint
+    // in progress when the snapshot was taken comes out a different length
+    let resumed = harness(seeded(Some(9)));
+    resumed
+        .app_state
+        .update_state(|s| *s = snapshot.state.clone())
+        .await;
+    resumed.spawn().await;
+    advance(60 * 90).await;
+    let mut schedule = schedule_of(&resumed).await;
+    schedule.truncate(expected.len());
+
+    let in_progress = snapshot.state.history.len();
+    assert_eq!(schedule[..in_progress], expected[..in_progress]);
+    assert_eq!(schedule[in_progress].0, expected[in_progress].0);
+    assert_eq!(schedule[in_progress + 1..], expected[in_progress + 1..]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn restored_session_keeps_its_shuffle_bag() {
+    let mut config = seeded(Some(3));
+    config.games.push(game("d.exe", "Game D"));
+    config.selection_strategy = SelectionStrategy::ShuffleBag;
+    config.min_swap = minutes(5);
+    config.max_swap = minutes(5);
+
+    let h = harness(config.clone());
+    h.spawn().await;
+    // Two picks into a round of four
+    advance(60 * 10 + 30).await;
+    let snapshot = h.app_state.get_state().await;
+    assert_eq!(snapshot.shuffle_bag.len(), 2);
+    advance(60 * 30).await;
+    let expected = schedule_of(&h).await;
+
+    let resumed = harness(config);
+    resumed
+        .app_state
+        .update_state(|s| *s = snapshot.clone())
+        .await;
+    resumed.spawn().await;
+    advance(60 * 60).await;
+    let mut schedule = schedule_of(&resumed).await;
+    schedule.truncate(expected.len());
+
+    let games = |schedule: &[(String, u64)]| -> Vec<String> {
+        schedule.iter().map(|(game, _)| game.clone()).collect()
+    };
+    assert_eq!(games(&schedule), games(&expected));
+}
+
+fn planning(ahead: u32) -> AppConfig {
+    let mut config = seeded(Some(5));
+    config.schedule_ahead = ahead;
+    config
+}
+
+/// Sleeps until the pending swap is done.
+async fn advance_past_swap(h: &Harness) {
+    let state = h.app_state.get_state().await;
+    let until = state.next_swap_at.unwrap() - h.app_state.clock.now();
+    advance(until.num_seconds() as u64 + 2).await;
+}
+
+async fn planned(h: &Harness) -> Vec<ScheduledSwap> {
+    h.app_state.get_state().await.schedule
+}
+
+fn no_repeats(current: Option<&str>, schedule: &[ScheduledSwap]) -> bool {
+    let mut previous = current;
+    schedule.iter().all(|entry| {
+        let repeat = previous == Some(entry.exe_name.as_str());
+        previous = Some(&entry.exe_name);
+        !repeat
+    })
+}
+
+#[tokio::test(start_paused = true)]
+async fn automatic_swaps_follow_the_schedule() {
+    let h = harness(planning(3));
+    h.spawn().await;
+    advance(1).await;
+
+    for _ in 0..5 {
+        let state = h.app_state.get_state().await;
+        assert_eq!(state.schedule.len(), 3);
+        assert!(no_repeats(state.current_exe.as_deref(), &state.schedule));
+
+        let next = state.schedule[0].clone();
+        advance_past_swap(&h).await;
+
+        let state = h.app_state.get_state().await;
+        assert_eq!(state.current_exe.as_deref(), Some(next.exe_name.as_str()));
+        let stint = state.next_swap_at.unwrap() - state.last_swap_at.unwrap();
+        assert_eq!(stint.num_seconds() as u64, next.duration_seconds);
+        assert!(state.schedule.iter().all(|e| e.id != next.id));
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn queued_games_go_before_the_schedule() {
+    let h = harness(planning(2));
+    h.spawn().await;
+    advance_past_swap(&h).await;
+
+    let state = h.app_state.get_state().await;
+    let current = state.current_exe.clone().unwrap();
+    let other = ["a.exe", "b.exe", "c.exe"]
+        .into_iter()
+        .find(|exe| *exe != current && *exe != state.schedule[0].exe_name)
+        .unwrap();
+    h.swapper.queue_next(Some(other)).await.unwrap();
+    advance_past_swap(&h).await;
+
+    let state = h.app_state.get_state().await;
+    assert_eq!(state.current_exe.as_deref(), Some(other));
+    assert_eq!(state.schedule.len(), 2);
+    assert!(no_repeats(state.current_exe.as_deref(), &state.schedule));
+}
+
+#[tokio::test(start_paused = true)]
+async fn moves_that_repeat_a_game_are_rejected() {
+    // With two games the schedule has to alternate, so any real move repeats one
+    let mut config = config(1, 10);
+    config.schedule_ahead = 3;
+    let h = harness(config);
+    h.spawn().await;
+
+    let schedule = planned(&h).await;
+    let last = schedule[2].id;
+    assert!(h.swapper.move_scheduled(last, 0).await.is_err());
+    assert!(h.swapper.move_scheduled(99, 0).await.is_err());
+    assert_eq!(planned(&h).await, schedule);
+
+    h.swapper.move_scheduled(last, 2).await.unwrap();
+    assert_eq!(planned(&h).await, schedule);
+}
+
+#[tokio::test(start_paused = true)]
+async fn scheduled_swaps_can_be_moved() {
+    let mut config = planning(5);
+    config.games.push(game("d.exe", "Game D"));
+    let h = harness(config);
+    h.spawn().await;
+    advance(1).await;
+
+    let current = h.app_state.get_state().await.current_exe;
+    let schedule = planned(&h).await;
+    // Find a move that keeps the rotation free of back to back repeats
+    let (id, to, expected) = (0..5)
+        .flat_map(|from| (0..5).map(move |to| (from, to)))
+        .filter(|(from, to)| from != to)
+        .find_map(|(from, to)| {
+            let mut moved = schedule.clone();
+            let entry = moved.remove(from);
+            moved.insert(to, entry);
+            no_repeats(current.as_deref(), &moved).then(|| (schedule[from].id, to, moved))
+        })
+        .unwrap();
+
+    h.swapper.move_scheduled(id, to).await.unwrap();
+    assert_eq!(planned(&h).await, expected);
+}
+
+#[tokio::test(start_paused = true)]
+async fn rerolls_replace_entries() {
+    let h = harness(planning(3));
+    h.spawn().await;
+
+    let schedule = planned(&h).await;
+    h.swapper
+        .reroll_scheduled(Some(schedule[1].id))
+        .await
+        .unwrap();
+    let rerolled = planned(&h).await;
+    assert_eq!(rerolled[0], schedule[0]);
+    assert_eq!(rerolled[1].id, schedule[1].id);
+    assert_eq!(rerolled[2], schedule[2]);
+    let current = h.app_state.get_state().await.current_exe;
+    assert!(no_repeats(current.as_deref(), &rerolled));
+    assert!(h.swapper.reroll_scheduled(Some(99)).await.is_err());
+
+    h.swapper.reroll_scheduled(None).await.unwrap();
+    let rerolled = planned(&h).await;
+    assert_eq!(rerolled.len(), 3);
+    assert!(rerolled
+        .iter()
+        .all(|e| schedule.iter().all(|s| s.id != e.id)));
+}
+
+#[tokio::test(start_paused = true)]
+async fn turning_planning_off_clears_the_schedule() {
+    let h = harness(planning(4));
+    h.spawn().await;
+    assert_eq!(planned(&h).await.len(), 4);
+
+    h.update_config(|c| c.schedule_ahead = 2).await;
+    assert_eq!(planned(&h).await.len(), 2);
+
+    h.update_config(|c| c.schedule_ahead = 0).await;
+    tokio::task::yield_now().await;
+    assert!(planned(&h).await.is_empty());
+}